@@ -19,47 +19,49 @@ fn main() -> Result<()> {
     let stop = std::time::Instant::now();
     println!("Elapsed time for fcl parsing using pest: {:?}", stop.duration_since(start));
 
+    // Compile the program into a controller and evaluate it
+    let mut controller = FuzzyController::from_fcl(fcl_program.as_str())?;
+    let heating = controller.evaluate(&[5.0, 30.0]);
+    println!("Heating value: {heating:?}");
+
     let ast = generate_ast(values.next().unwrap());
     println!("{ast:#?}");
-    Ok(())
 
-//     let fll_program = r"#from examples/mamdani/ObstacleAvoidance.fll  
-// Engine: ObstacleAvoidance
-// description: An engine for obstacle avoidance
-// InputVariable: obstacle
-//   description: direction of the obstacle
-//   enabled: true
-//   range: 0.000 1.000
-//   lock-range: false
-//   term: left Ramp 1.000 0.000
-//   term: right Ramp 0.000 1.000
-// OutputVariable: mSteer
-//   description: steering direction of the vehicle
-//   enabled: true
-//   range: 0.000 1.000
-//   lock-range: false
-//   aggregation: Maximum
-//   defuzzifier: Centroid 100
-//   default: nan
-//   lock-previous: false
-//   term: left Ramp 1.000 0.000
-//   term: right Ramp 0.000 1.000
-// RuleBlock: mamdani
-//   description: Mamdani inference for steering
-//   enabled: true
-//   conjunction: none
-//   disjunction: none
-//   implication: AlgebraicProduct
-//   activation: General
-//   rule: if obstacle is left then mSteer is right
-//   rule: if obstacle is right then mSteer is left";
-//
-//     let values = fll_parser(fll_program);
-//     if let Ok(rules) = values {
-//         println!("{rules:?}");
-//     } else {
-//         eprintln!("Error while parsing rules in FCL: {values:?}")
-//     }
+    let fll_program = r"#from examples/mamdani/ObstacleAvoidance.fll
+Engine: ObstacleAvoidance
+description: An engine for obstacle avoidance
+InputVariable: obstacle
+  description: direction of the obstacle
+  enabled: true
+  range: 0.000 1.000
+  lock-range: false
+  term: left Ramp 1.000 0.000
+  term: right Ramp 0.000 1.000
+OutputVariable: mSteer
+  description: steering direction of the vehicle
+  enabled: true
+  range: 0.000 1.000
+  lock-range: false
+  aggregation: Maximum
+  defuzzifier: Centroid 100
+  default: nan
+  lock-previous: false
+  term: left Ramp 1.000 0.000
+  term: right Ramp 0.000 1.000
+RuleBlock: mamdani
+  description: Mamdani inference for steering
+  enabled: true
+  conjunction: none
+  disjunction: none
+  implication: AlgebraicProduct
+  activation: General
+  rule: if obstacle is left then mSteer is right
+  rule: if obstacle is right then mSteer is left";
+
+    // FLL engines are parsed into the same rule model as FCL programs
+    let engine = parse_fll(fll_program)?;
+    println!("{engine:#?}");
+    Ok(())
 
     // Define FuzzySet
     // println!("Trust value: {:?}", values);
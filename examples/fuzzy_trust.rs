@@ -11,75 +11,106 @@ fn main() {
     let reputation = FuzzyVariable::new(
         "reputation",
         vec![low_trust.clone(), medium_trust.clone(), high_trust.clone()],
-        Rc::new(|_| 0.0),
     );
     let transaction_history = FuzzyVariable::new(
         "transaction_history",
         vec![low_trust.clone(), medium_trust.clone(), high_trust.clone()],
-        Rc::new(|_| 0.0),
     );
 
+    // The trust level defuzzifies the aggregated output over its range, with a centroid unless
+    // another defuzzifier is set
     let trust_level = FuzzyVariable::new(
         "trust_level",
         vec![low_trust.clone(), medium_trust.clone(), high_trust.clone()],
-        Rc::new(|total_weight| total_weight / 3.0),
-    );
+    )
+    .with_range(0.0, 10.0);
+    // The same rules conclude on how closely to monitor the transactions
+    let monitoring_intensity = FuzzyVariable::new(
+        "monitoring_intensity",
+        vec![low_trust.clone(), medium_trust.clone(), high_trust.clone()],
+    )
+    .with_range(0.0, 10.0);
 
-    // Define rules to infer member ship
+    // Define rules to infer member ship, by the names of the variables and of their terms
     let rule1 = Rule::new(
-        vec![low_trust.clone(), low_trust.clone()],
-        low_trust.clone(),
-    );
+        &[("reputation", "low"), ("transaction_history", "low")],
+        ("trust_level", "low"),
+    )
+    .with_consequent(("monitoring_intensity", "high"));
     let rule2 = Rule::new(
-        vec![low_trust.clone(), medium_trust.clone()],
-        low_trust.clone(),
-    );
+        &[("reputation", "low"), ("transaction_history", "medium")],
+        ("trust_level", "low"),
+    )
+    .with_consequent(("monitoring_intensity", "high"));
     let rule3 = Rule::new(
-        vec![medium_trust.clone(), low_trust.clone()],
-        low_trust.clone(),
-    );
+        &[("reputation", "medium"), ("transaction_history", "low")],
+        ("trust_level", "low"),
+    )
+    .with_consequent(("monitoring_intensity", "high"));
     let rule4 = Rule::new(
-        vec![medium_trust.clone(), medium_trust.clone()],
-        medium_trust.clone(),
-    );
+        &[("reputation", "medium"), ("transaction_history", "medium")],
+        ("trust_level", "medium"),
+    )
+    .with_consequent(("monitoring_intensity", "medium"));
     let rule5 = Rule::new(
-        vec![high_trust.clone(), low_trust.clone()],
-        medium_trust.clone(),
-    );
+        &[("reputation", "high"), ("transaction_history", "low")],
+        ("trust_level", "medium"),
+    )
+    .with_consequent(("monitoring_intensity", "medium"));
     let rule6 = Rule::new(
-        vec![low_trust.clone(), high_trust.clone()],
-        medium_trust.clone(),
-    );
+        &[("reputation", "low"), ("transaction_history", "high")],
+        ("trust_level", "medium"),
+    )
+    .with_consequent(("monitoring_intensity", "medium"));
     let rule7 = Rule::new(
-        vec![high_trust.clone(), medium_trust.clone()],
-        high_trust.clone(),
-    );
+        &[("reputation", "high"), ("transaction_history", "medium")],
+        ("trust_level", "high"),
+    )
+    .with_consequent(("monitoring_intensity", "low"));
     let rule8 = Rule::new(
-        vec![medium_trust.clone(), high_trust.clone()],
-        high_trust.clone(),
-    );
+        &[("reputation", "medium"), ("transaction_history", "high")],
+        ("trust_level", "high"),
+    )
+    .with_consequent(("monitoring_intensity", "low"));
     let rule9 = Rule::new(
-        vec![high_trust.clone(), high_trust.clone()],
-        high_trust.clone(),
-    );
+        &[("reputation", "high"), ("transaction_history", "high")],
+        ("trust_level", "high"),
+    )
+    .with_consequent(("monitoring_intensity", "low"));
 
     // Using the mamdani fuzzy inference system
     let mamdani = Mamdani::new(
         vec![reputation, transaction_history],
-        trust_level,
+        vec![trust_level, monitoring_intensity],
         vec![
             rule1, rule2, rule3, rule4, rule5, rule6, rule7, rule8, rule9,
         ],
-    );
+    )
+    .expect("rules name the variables of the system and their terms");
 
     // Create the controller for our trust engine
-    let trust_controller = FuzzyController::new(mamdani);
+    let mut trust_controller = FuzzyController::new(mamdani);
 
     // Set the initial values
     let reputation_value = 7.5;
     let transaction_history_value = 4.0;
 
     // Evaluate the values and get the final result
-    let trust_value = trust_controller.evaluate(&[reputation_value, transaction_history_value]);
-    println!("Trust value: {:?}", trust_value);
+    let outputs = trust_controller.evaluate_outputs(&[reputation_value, transaction_history_value]);
+    println!("Trust value: {:?}", outputs["trust_level"]);
+    println!("Monitoring intensity: {:?}", outputs["monitoring_intensity"]);
+
+    // Compare with the mean of maximum of the same aggregated trust level
+    let aggregated = trust_controller
+        .infer
+        .aggregate(&[reputation_value, transaction_history_value]);
+    let mean_of_maximum = defuzzifier::MeanOfMaximum::default().defuzzify(&aggregated[0]);
+    println!("Trust value (mean of maximum): {:?}", mean_of_maximum);
+
+    // Explain the decisions to the people reviewing them
+    let justifications = Justifier::new()
+        .justify(&trust_controller.infer, &[reputation_value, transaction_history_value]);
+    for justification in justifications {
+        println!("{justification}");
+    }
 }
@@ -1,47 +1,323 @@
+pub mod activation;
 pub mod controller;
+pub mod counterfactual;
+pub mod defuzzifier;
 pub mod functions;
+pub mod hedges;
 pub mod inference;
+pub mod justification;
 pub mod control_language;
+pub mod membership;
+pub mod network;
+pub mod norms;
 
+use std::fmt;
 use std::rc::Rc;
 
+use serde::Serialize;
+
+pub use activation::{Activation, Comparison};
 pub use controller::*;
+pub use counterfactual::{Change, Counterfactual, CounterfactualSearch};
+pub use defuzzifier::Defuzzifier;
 pub use control_language::*;
+pub use hedges::Hedge;
+pub use justification::Justifier;
+pub use membership::MembershipFunction;
+pub use network::{FuzzyNetwork, NetworkTrace, SystemTrace};
+pub use norms::{SNorm, TNorm};
+
+
+/// `variable IS term`, naming a term of a variable
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Proposition {
+    pub variable: String,
+    pub term: String,
+}
+
+impl Proposition {
+    pub fn new(variable: &str, term: &str) -> Self {
+        Self {
+            variable: variable.to_string(),
+            term: term.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Proposition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} IS {}", self.variable, self.term)
+    }
+}
+
+/// Condition of a rule, evaluated with the norms of its rule block
+#[derive(Clone)]
+pub enum RuleExpr {
+    /// `variable IS term`, the membership of the input to the term
+    Is(Proposition),
+    /// Complement of the truth of the operand
+    Not(Box<RuleExpr>),
+    /// Truth of the operand modified by a hedge, `very`, `somewhat`, ...
+    Hedged(Rc<dyn Hedge>, Box<RuleExpr>),
+    /// Conjunction of the operands, true when there are none
+    And(Vec<RuleExpr>),
+    /// Disjunction of the operands, false when there are none
+    Or(Vec<RuleExpr>),
+}
 
+impl RuleExpr {
+    pub fn is(variable: &str, term: &str) -> Self {
+        RuleExpr::Is(Proposition::new(variable, term))
+    }
+
+    /// Every proposition tested by the condition, from left to right
+    pub fn propositions(&self) -> Vec<&Proposition> {
+        match self {
+            RuleExpr::Is(proposition) => vec![proposition],
+            RuleExpr::Not(operand) | RuleExpr::Hedged(_, operand) => operand.propositions(),
+            RuleExpr::And(operands) | RuleExpr::Or(operands) => operands
+                .iter()
+                .flat_map(|operand| operand.propositions())
+                .collect(),
+        }
+    }
+
+    /// Truth of the condition given the `membership` of each proposition, conjuncted and
+    /// disjuncted with the norms of `block`
+    pub fn evaluate(&self, block: &RuleBlock, membership: &dyn Fn(&Proposition) -> f64) -> f64 {
+        match self {
+            RuleExpr::Is(proposition) => membership(proposition),
+            RuleExpr::Not(operand) => 1.0 - operand.evaluate(block, membership),
+            RuleExpr::Hedged(hedge, operand) => hedge.apply(operand.evaluate(block, membership)),
+            RuleExpr::And(operands) => operands
+                .iter()
+                .map(|operand| operand.evaluate(block, membership))
+                .fold(1.0, |a, b| block.conjunction.compute(a, b)),
+            RuleExpr::Or(operands) => operands
+                .iter()
+                .map(|operand| operand.evaluate(block, membership))
+                .fold(0.0, |a, b| block.disjunction.compute(a, b)),
+        }
+    }
+}
+
+/// Condition written as in FCL with its nested conjunctions and disjunctions parenthesized, and
+/// hedges before the term they modify, `food IS very good`
+impl fmt::Display for RuleExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |f: &mut fmt::Formatter<'_>, operands: &[RuleExpr], connective| {
+            for (index, operand) in operands.iter().enumerate() {
+                if index > 0 {
+                    write!(f, " {connective} ")?;
+                }
+                match operand {
+                    RuleExpr::And(nested) | RuleExpr::Or(nested) if nested.len() > 1 => {
+                        write!(f, "({operand})")?
+                    }
+                    operand => write!(f, "{operand}")?,
+                }
+            }
+            Ok(())
+        };
+        match self {
+            RuleExpr::Is(proposition) => write!(f, "{proposition}"),
+            RuleExpr::Not(operand) => match &**operand {
+                RuleExpr::Is(Proposition { variable, term }) => {
+                    write!(f, "{variable} IS NOT {term}")
+                }
+                operand => write!(f, "NOT ({operand})"),
+            },
+            RuleExpr::Hedged(hedge, operand) => match &**operand {
+                RuleExpr::Is(Proposition { variable, term }) => {
+                    write!(f, "{variable} IS {} {term}", hedge.name())
+                }
+                operand => write!(f, "{} ({operand})", hedge.name()),
+            },
+            RuleExpr::And(operands) => join(f, operands, "AND"),
+            RuleExpr::Or(operands) => join(f, operands, "OR"),
+        }
+    }
+}
 
 /// Structure to define a rule for membership of a variable.
+/// The rule concludes its consequents as much as its condition holds, the inputs it does not
+/// test have no effect on it
 pub struct Rule {
-    antecedents: Vec<FuzzySet>,
-    consequent: FuzzySet,
+    condition: RuleExpr,
+    consequents: Vec<Proposition>,
+    weight: f64,
 }
 
 impl Rule {
-    pub fn new(antecedents: Vec<FuzzySet>, consequent: FuzzySet) -> Self {
+    /// Rule of `(variable, term)` antecedents all holding, resolved against the variables of
+    /// the system when building it
+    pub fn new(antecedents: &[(&str, &str)], consequent: (&str, &str)) -> Self {
+        let antecedents = antecedents
+            .iter()
+            .map(|&(variable, term)| RuleExpr::is(variable, term))
+            .collect();
+        Self::with_condition(RuleExpr::And(antecedents), consequent)
+    }
+
+    pub fn with_condition(condition: RuleExpr, consequent: (&str, &str)) -> Self {
         Self {
-            antecedents,
-            consequent,
+            condition,
+            consequents: vec![Proposition::new(consequent.0, consequent.1)],
+            weight: 1.0,
         }
     }
+
+    /// Adds a consequent concluded as much as the others, `THEN a IS x, b IS y`
+    pub fn with_consequent(mut self, consequent: (&str, &str)) -> Self {
+        self.consequents
+            .push(Proposition::new(consequent.0, consequent.1));
+        self
+    }
+
+    /// Sets the certainty of the rule in `[0, 1]`, scaling the truth of its condition before the
+    /// implication, as FCL `WITH weight`
+    pub fn with_weight(mut self, weight: f64) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    pub fn weight(&self) -> f64 {
+        self.weight
+    }
+
+    /// Truth of the condition scaled by the weight of the rule, see [`RuleExpr::evaluate`]
+    pub fn activation(
+        &self,
+        block: &RuleBlock,
+        membership: &dyn Fn(&Proposition) -> f64,
+    ) -> f64 {
+        self.weight * self.condition.evaluate(block, membership)
+    }
+
+    pub fn condition(&self) -> &RuleExpr {
+        &self.condition
+    }
+
+    pub fn consequents(&self) -> &[Proposition] {
+        &self.consequents
+    }
+}
+
+/// Rules sharing the norms combining their memberships, as an FCL RULEBLOCK
+pub struct RuleBlock {
+    pub name: String,
+    /// T-norm conjuncting the operands of `AND`
+    pub conjunction: Rc<dyn TNorm>,
+    /// S-norm disjuncting the operands of `OR`
+    pub disjunction: Rc<dyn SNorm>,
+    /// T-norm applying the firing strength of a rule to its consequent, the minimum clips it
+    /// (Mamdani) and the algebraic product scales it (Larsen)
+    pub implication: Rc<dyn TNorm>,
+    /// S-norm accumulating the conclusions of the rules into the output
+    pub accumulation: Rc<dyn SNorm>,
+    /// Rules that conclude and the degree they conclude at
+    pub activation: Activation,
+    pub rules: Vec<Rule>,
+}
+
+impl RuleBlock {
+    /// Rule block conjuncting and implying with the minimum, disjuncting and accumulating with
+    /// the maximum, where every rule concludes, see [`Activation::General`]
+    pub fn new(name: &str, rules: Vec<Rule>) -> Self {
+        Self {
+            name: name.to_string(),
+            conjunction: Rc::new(norms::Minimum),
+            disjunction: Rc::new(norms::Maximum),
+            implication: Rc::new(norms::Minimum),
+            accumulation: Rc::new(norms::Maximum),
+            activation: Activation::General,
+            rules,
+        }
+    }
+
+    pub fn with_conjunction(mut self, conjunction: Rc<dyn TNorm>) -> Self {
+        self.conjunction = conjunction;
+        self
+    }
+
+    pub fn with_disjunction(mut self, disjunction: Rc<dyn SNorm>) -> Self {
+        self.disjunction = disjunction;
+        self
+    }
+
+    pub fn with_implication(mut self, implication: Rc<dyn TNorm>) -> Self {
+        self.implication = implication;
+        self
+    }
+
+    pub fn with_accumulation(mut self, accumulation: Rc<dyn SNorm>) -> Self {
+        self.accumulation = accumulation;
+        self
+    }
+
+    pub fn with_activation(mut self, activation: Activation) -> Self {
+        self.activation = activation;
+        self
+    }
 }
 
 /// Struct to represent fuzzy set definitions
 #[derive(Clone)]
 pub struct FuzzySet {
     pub name: String,
-    pub membership_fn: Rc<dyn Fn(f64) -> f64>,
+    pub function: MembershipFunction,
+    /// Scale of the membership of the function in `[0, 1]`, 1 unless an FLL term sets it
+    pub height: f64,
 }
 
 impl FuzzySet {
+    /// Fuzzy set of an opaque membership function, see [`FuzzySet::with_function`]
     pub fn new(name: &str, membership_fn: Rc<dyn Fn(f64) -> f64>) -> Self {
+        Self::with_function(name, MembershipFunction::Custom(membership_fn))
+    }
+
+    pub fn with_function(name: &str, function: MembershipFunction) -> Self {
         Self {
             name: name.to_string(),
-            membership_fn,
+            function,
+            height: 1.0,
         }
     }
 
+    /// Scales the membership of the function by `height`
+    pub fn with_height(mut self, height: f64) -> Self {
+        self.height = height;
+        self
+    }
+
+    /// Fuzzy set whose membership interpolates linearly between `points` sorted by `x`
+    pub fn from_points(name: &str, points: Vec<(f64, f64)>) -> Self {
+        Self::with_function(
+            name,
+            MembershipFunction::PiecewiseLinear {
+                points: points.into(),
+            },
+        )
+    }
+
     /// Evaluate the membership of a value
     pub fn membership(&self, value: f64) -> f64 {
-        (self.membership_fn)(value)
+        self.height * self.function.evaluate(value)
+    }
+
+    /// Points of the function scaled by the height, see [`MembershipFunction::points`]
+    pub fn points(&self) -> Option<Vec<(f64, f64)>> {
+        let points = self.function.points()?;
+        Some(points.into_iter().map(|(x, y)| (x, self.height * y)).collect())
+    }
+
+    /// Value whose membership is `y`, the inverse of the function at `y` over the height, see
+    /// [`MembershipFunction::inverse`]. `None` when the height is 0
+    pub fn inverse(&self, y: f64) -> Option<f64> {
+        if self.height <= 0.0 {
+            return None;
+        }
+        self.function.inverse(y / self.height)
     }
 }
 
@@ -49,15 +325,196 @@ impl FuzzySet {
 pub struct FuzzyVariable {
     name: String,
     fuzzy_sets: Vec<FuzzySet>,
-    pub defuzz_fn: Rc<dyn Fn(f64) -> f64>,
+    range: Option<(f64, f64)>,
+    resolution: Option<usize>,
+    lock_range: bool,
+    default: f64,
+    lock_previous: bool,
+    /// Turns the aggregated output into the inferred value of an output variable
+    pub defuzzifier: Rc<dyn Defuzzifier>,
 }
 
 impl FuzzyVariable {
-    pub fn new(name: &str, fuzzy_sets: Vec<FuzzySet>, defuzz_fn: Rc<dyn Fn(f64) -> f64>) -> Self {
+    /// Variable defuzzified with a [`Centroid`](defuzzifier::Centroid) when it is an output, NaN
+    /// when no rule fires on it
+    pub fn new(name: &str, fuzzy_sets: Vec<FuzzySet>) -> Self {
         Self {
             name: name.to_string(),
             fuzzy_sets,
-            defuzz_fn,
+            range: None,
+            resolution: None,
+            lock_range: false,
+            default: f64::NAN,
+            lock_previous: false,
+            defuzzifier: Rc::new(defuzzifier::Centroid::default()),
+        }
+    }
+
+    pub fn with_defuzzifier(mut self, defuzzifier: Rc<dyn Defuzzifier>) -> Self {
+        self.defuzzifier = defuzzifier;
+        self
+    }
+
+    /// Sets the universe of the variable to `[min, max]`
+    pub fn with_range(mut self, min: f64, max: f64) -> Self {
+        self.range = Some((min, max));
+        self
+    }
+
+    /// Sets the number of intervals dividing the range in the sampled view of the variable
+    pub fn with_resolution(mut self, resolution: usize) -> Self {
+        self.resolution = Some(resolution);
+        self
+    }
+
+    /// Clamps the values of the variable to its range, the inputs before fuzzifying them and the
+    /// outputs once defuzzified
+    pub fn with_lock_range(mut self, lock_range: bool) -> Self {
+        self.lock_range = lock_range;
+        self
+    }
+
+    /// Sets the value of the output when no rule fires on it, NaN by default
+    pub fn with_default(mut self, default: f64) -> Self {
+        self.default = default;
+        self
+    }
+
+    /// Keeps the previous value of the output when no rule fires on it, see
+    /// [`FuzzyController::evaluate_outputs`]
+    pub fn with_lock_previous(mut self, lock_previous: bool) -> Self {
+        self.lock_previous = lock_previous;
+        self
+    }
+
+    /// Number of intervals dividing the range in the sampled view of the variable, see
+    /// [`FuzzyVariable::universe`]
+    pub fn resolution(&self) -> usize {
+        self.resolution.unwrap_or(defuzzifier::DEFAULT_RESOLUTION)
+    }
+
+    pub fn lock_range(&self) -> bool {
+        self.lock_range
+    }
+
+    /// Value of the output when no rule fires on it
+    pub fn default(&self) -> f64 {
+        self.default
+    }
+
+    pub fn lock_previous(&self) -> bool {
+        self.lock_previous
+    }
+
+    /// `value` clamped to the range when it is locked, `value` otherwise. NaN is kept
+    pub fn clamp(&self, value: f64) -> f64 {
+        match self.range() {
+            Some((min, max)) if self.lock_range && !value.is_nan() => value.max(min).min(max),
+            _ => value,
         }
     }
+
+    /// `resolution + 1` evenly spaced values from the start to the end of the range
+    pub fn universe(&self) -> Option<Vec<f64>> {
+        let (min, max) = self.range()?;
+        let resolution = self.resolution().max(1);
+        let dx = (max - min) / resolution as f64;
+        Some(
+            (0..=resolution)
+                .map(|i| match i {
+                    i if i == resolution => max,
+                    i => min + i as f64 * dx,
+                })
+                .collect(),
+        )
+    }
+
+    /// Membership of every value of the universe to each set, in the order of the sets
+    pub fn sampled_sets(&self) -> Option<Vec<(&str, Vec<f64>)>> {
+        let universe = self.universe()?;
+        let sampled = self
+            .fuzzy_sets
+            .iter()
+            .map(|set| {
+                let memberships = universe.iter().map(|&x| set.membership(x)).collect();
+                (set.name.as_str(), memberships)
+            })
+            .collect();
+        Some(sampled)
+    }
+
+    /// Universe of the variable, the one set by [`FuzzyVariable::with_range`] or else the
+    /// smallest interval holding the points and the finite bounds of the supports of its sets.
+    /// `None` when neither gives a non-empty interval
+    pub fn range(&self) -> Option<(f64, f64)> {
+        if self.range.is_some() {
+            return self.range;
+        }
+        let (min, max) = self
+            .fuzzy_sets
+            .iter()
+            .flat_map(|set| match set.function.points() {
+                Some(points) => points.iter().map(|&(x, _)| x).collect(),
+                None => set
+                    .function
+                    .support()
+                    .map_or(vec![], |(start, end)| vec![start, end]),
+            })
+            .filter(|x| x.is_finite())
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), x| {
+                (min.min(x), max.max(x))
+            });
+        (min < max).then_some((min, max))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn fuzzy_sets(&self) -> &[FuzzySet] {
+        &self.fuzzy_sets
+    }
+
+    /// Set of the variable named `name`
+    pub fn set(&self, name: &str) -> Option<&FuzzySet> {
+        self.fuzzy_sets.iter().find(|set| set.name == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level() -> FuzzyVariable {
+        let low = FuzzySet::from_points("low", vec![(0.0, 1.0), (10.0, 0.0)]);
+        let high = FuzzySet::from_points("high", vec![(0.0, 0.0), (10.0, 1.0)]);
+        FuzzyVariable::new("level", vec![low, high])
+    }
+
+    #[test]
+    fn test_range() {
+        assert_eq!(level().range(), Some((0.0, 10.0)));
+        assert_eq!(level().with_range(-5.0, 5.0).range(), Some((-5.0, 5.0)));
+        let constant = FuzzySet::with_function("any", MembershipFunction::Constant { value: 1.0 });
+        assert_eq!(FuzzyVariable::new("any", vec![constant]).range(), None);
+    }
+
+    #[test]
+    fn test_clamp() {
+        assert_eq!(level().clamp(12.0), 12.0);
+        let locked = level().with_lock_range(true);
+        assert_eq!(locked.clamp(12.0), 10.0);
+        assert_eq!(locked.clamp(-1.0), 0.0);
+        assert_eq!(locked.clamp(4.0), 4.0);
+    }
+
+    #[test]
+    fn test_sampled_sets() {
+        let level = level().with_resolution(4);
+        assert_eq!(level.universe(), Some(vec![0.0, 2.5, 5.0, 7.5, 10.0]));
+        let sampled = level.sampled_sets().unwrap();
+        assert_eq!(sampled[0], ("low", vec![1.0, 0.75, 0.5, 0.25, 0.0]));
+        assert_eq!(sampled[1], ("high", vec![0.0, 0.25, 0.5, 0.75, 1.0]));
+        assert_eq!(FuzzyVariable::new("empty", vec![]).universe(), None);
+    }
 }
@@ -0,0 +1,193 @@
+//! Activation methods choosing the rules of a block that conclude and the degree they conclude
+//! at from their firing strengths.
+//!
+//! The methods and their names follow fuzzylite.
+
+/// Comparison of the firing strength of a rule to the value of a [`Activation::Threshold`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    LessThan,
+    LessOrEqual,
+    Equal,
+    NotEqual,
+    GreaterOrEqual,
+    GreaterThan,
+}
+
+impl Comparison {
+    /// Comparison written as in fuzzylite, `<`, `<=`, `==`, `!=`, `>=` or `>`
+    pub fn from_operator(operator: &str) -> Option<Self> {
+        Some(match operator {
+            "<" => Self::LessThan,
+            "<=" => Self::LessOrEqual,
+            "==" => Self::Equal,
+            "!=" => Self::NotEqual,
+            ">=" => Self::GreaterOrEqual,
+            ">" => Self::GreaterThan,
+            _ => return None,
+        })
+    }
+
+    pub fn operator(self) -> &'static str {
+        match self {
+            Self::LessThan => "<",
+            Self::LessOrEqual => "<=",
+            Self::Equal => "==",
+            Self::NotEqual => "!=",
+            Self::GreaterOrEqual => ">=",
+            Self::GreaterThan => ">",
+        }
+    }
+
+    /// `a` compared to `b`
+    pub fn compare(self, a: f64, b: f64) -> bool {
+        match self {
+            Self::LessThan => a < b,
+            Self::LessOrEqual => a <= b,
+            Self::Equal => a == b,
+            Self::NotEqual => a != b,
+            Self::GreaterOrEqual => a >= b,
+            Self::GreaterThan => a > b,
+        }
+    }
+}
+
+/// Rules of a block that conclude, the others are skipped
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Activation {
+    /// Every rule, at its firing strength
+    #[default]
+    General,
+    /// The first `count` rules, in their order, of positive strength at least `threshold`
+    First { count: usize, threshold: f64 },
+    /// The last `count` rules, in their order, of positive strength at least `threshold`
+    Last { count: usize, threshold: f64 },
+    /// The `count` rules of highest positive strength
+    Highest(usize),
+    /// The `count` rules of lowest positive strength
+    Lowest(usize),
+    /// The rules whose strength compares to the value
+    Threshold(Comparison, f64),
+    /// Every rule, at its strength divided by the total strength of the block
+    Proportional,
+}
+
+impl Activation {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::General => "General",
+            Self::First { .. } => "First",
+            Self::Last { .. } => "Last",
+            Self::Highest(_) => "Highest",
+            Self::Lowest(_) => "Lowest",
+            Self::Threshold(..) => "Threshold",
+            Self::Proportional => "Proportional",
+        }
+    }
+
+    /// Threshold of the strengths in `[0, 1]`, `None` for methods without one
+    pub fn threshold(&self) -> Option<f64> {
+        match *self {
+            Self::First { threshold, .. }
+            | Self::Last { threshold, .. }
+            | Self::Threshold(_, threshold) => Some(threshold),
+            _ => None,
+        }
+    }
+
+    /// Degree each rule concludes at from the firing `strengths` of the rules of a block, `None`
+    /// for the rules that are not activated
+    pub fn activate(&self, strengths: &[f64]) -> Vec<Option<f64>> {
+        let mut activated = vec![None; strengths.len()];
+        let mut activate = |indices: &mut dyn Iterator<Item = usize>, count: usize| {
+            for index in indices.take(count) {
+                activated[index] = Some(strengths[index]);
+            }
+        };
+        let positive = |index: &usize| strengths[*index] > 0.0;
+        match *self {
+            Self::General => return strengths.iter().copied().map(Some).collect(),
+            Self::First { count, threshold } => activate(
+                &mut (0..strengths.len())
+                    .filter(positive)
+                    .filter(|&index| strengths[index] >= threshold),
+                count,
+            ),
+            Self::Last { count, threshold } => activate(
+                &mut (0..strengths.len())
+                    .rev()
+                    .filter(positive)
+                    .filter(|&index| strengths[index] >= threshold),
+                count,
+            ),
+            Self::Highest(count) | Self::Lowest(count) => {
+                let mut ranked: Vec<_> = (0..strengths.len()).filter(positive).collect();
+                // The sort is stable, the first rules win among equal strengths
+                ranked.sort_by(|&a, &b| match self {
+                    Self::Highest(_) => strengths[b].total_cmp(&strengths[a]),
+                    _ => strengths[a].total_cmp(&strengths[b]),
+                });
+                activate(&mut ranked.into_iter(), count)
+            }
+            Self::Threshold(comparison, value) => activate(
+                &mut (0..strengths.len())
+                    .filter(|&index| comparison.compare(strengths[index], value)),
+                strengths.len(),
+            ),
+            Self::Proportional => {
+                let total: f64 = strengths.iter().sum();
+                return strengths
+                    .iter()
+                    .map(|&strength| Some(if total > 0.0 { strength / total } else { 0.0 }))
+                    .collect();
+            }
+        }
+        activated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STRENGTHS: [f64; 5] = [0.5, 0.0, 0.25, 1.0, 0.25];
+
+    fn activated(activation: Activation) -> Vec<usize> {
+        activation
+            .activate(&STRENGTHS)
+            .iter()
+            .enumerate()
+            .filter_map(|(index, degree)| degree.map(|_| index))
+            .collect()
+    }
+
+    #[test]
+    fn test_activation() {
+        assert_eq!(activated(Activation::General), vec![0, 1, 2, 3, 4]);
+        let first = Activation::First {
+            count: 2,
+            threshold: 0.3,
+        };
+        assert_eq!(activated(first), vec![0, 3]);
+        let last = Activation::Last {
+            count: 2,
+            threshold: 0.0,
+        };
+        assert_eq!(activated(last), vec![3, 4]);
+        assert_eq!(activated(Activation::Highest(2)), vec![0, 3]);
+        assert_eq!(activated(Activation::Lowest(2)), vec![2, 4]);
+        assert_eq!(activated(Activation::Lowest(1)), vec![2]);
+        let threshold = Activation::Threshold(Comparison::LessThan, 0.5);
+        assert_eq!(activated(threshold), vec![1, 2, 4]);
+        let proportional = Activation::Proportional.activate(&STRENGTHS);
+        assert_eq!(proportional[0], Some(0.25));
+        assert_eq!(proportional[1], Some(0.0));
+        assert_eq!(Activation::Proportional.activate(&[0.0]), vec![Some(0.0)]);
+        for operator in ["<", "<=", "==", "!=", ">=", ">"] {
+            assert_eq!(
+                Comparison::from_operator(operator).unwrap().operator(),
+                operator
+            );
+        }
+    }
+}
@@ -1,155 +1,468 @@
-use std::ops::Range;
-
-use pest::{iterators::*, Parser};
+use pest::{iterators::*, Parser, RuleType, Span};
 use pest_derive::Parser;
 
-use anyhow::Result;
+pub mod ast;
+pub mod compiler;
+pub mod diagnostics;
+pub mod fll;
+pub mod writer;
+use ast::*;
+pub use compiler::{
+    compile_fcl, compile_fcl_blocks, compile_fcl_sugeno, compile_fll, compile_fll_sugeno,
+};
+pub use diagnostics::{check_fcl, Diagnostic, Diagnostics};
+pub use fll::{fll_parser, parse_fll, FLLParser};
+pub use writer::write_fcl;
+
+type Result<T, E = Diagnostic> = std::result::Result<T, E>;
 
 #[derive(Parser)]
 #[grammar = "fuzzy_logic/grammar/fcl.pest"]
 pub struct FCLParser;
 
-/// Variable types used in the FCL grammar
-#[derive(Debug, Clone, Copy)]
-pub enum VarType {
-    Number,
-    Boolean,
-}
-
-/// Node types used to generate the Abstract Syntax Tree
-#[derive(Debug, Clone)]
-pub enum AstNode {
-    FunctionBlock(Vec<AstNode>),
-    Block(String, Vec<AstNode>),
-    VarOutput(Vec<AstNode>),
-    VarInput(Vec<AstNode>),
-    Antecedent(Vec<AstNode>),
-    Consequent(Vec<AstNode>),
-    Identifier {
-        name: String,
-    },
-    Number(f64),
-    RuleBlock {
-        rules: Vec<AstNode>,
-        function_ops: Vec<AstNode>,
-    },
-    Operator {
-        name: String,
-    },
-    Term {
-        name: String,
-        envelope: Vec<Range<f64>>,
-    },
-    Rule {
-        antecedent: Box<AstNode>,
-        consequent: Box<AstNode>,
-    },
-    VarType(VarType),
-    EOI,
-}
-
 /// Parses the input string and returns rules from the FCL grammar
-pub fn fcl_parser(input: &str) -> Result<Pairs<Rule>, pest::error::Error<Rule>> {
+pub fn fcl_parser(input: &str) -> Result<Pairs<'_, Rule>, pest::error::Error<Rule>> {
     let pairs = FCLParser::parse(Rule::fcl, input)?;
     Ok(pairs)
 }
 
-/// Generates recusively an AST from the grammar rules
-pub fn generate_ast(pair: Pair<Rule>) -> anyhow::Result<AstNode> {
+/// Parses an FCL program into the function blocks it defines
+pub fn parse_fcl(input: &str) -> Result<Vec<FunctionBlock<'_>>> {
+    match fcl_parser(input)?.next() {
+        Some(fcl) => generate_ast(fcl),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Generates the function blocks of an `fcl` or `function_block` pair
+pub fn generate_ast(pair: Pair<Rule>) -> Result<Vec<FunctionBlock>> {
     match pair.as_rule() {
-        Rule::fcl | Rule::function_block => {
-            let mut function_blocks = Vec::new();
-            for fb in pair.into_inner() {
-                function_blocks.push(generate_ast(fb)?);
-            }
-            Ok(AstNode::FunctionBlock(function_blocks))
+        Rule::fcl => pair
+            .into_inner()
+            .filter(|pair| pair.as_rule() == Rule::function_block)
+            .map(function_block)
+            .collect(),
+        Rule::function_block => Ok(vec![function_block(pair)?]),
+        other => Err(Diagnostic::new(
+            format!("expected a function block, found {other:?}"),
+            pair.as_span(),
+        )),
+    }
+}
+
+/// Next inner pair of a node, the grammar guarantees its presence for well-formed trees
+fn next<'i, R: RuleType>(inner: &mut Pairs<'i, R>, span: Span<'i>) -> Result<Pair<'i, R>> {
+    inner
+        .next()
+        .ok_or_else(|| Diagnostic::new("incomplete construct", span))
+}
+
+fn identifier<R: RuleType>(pair: Pair<R>) -> Identifier {
+    Identifier {
+        name: pair.as_str(),
+        span: pair.as_span(),
+    }
+}
+
+fn function_block(pair: Pair<Rule>) -> Result<FunctionBlock> {
+    let span = pair.as_span();
+    let mut inner = pair.into_inner();
+    let mut function_block = FunctionBlock {
+        name: identifier(next(&mut inner, span)?),
+        inputs: Vec::new(),
+        outputs: Vec::new(),
+        fuzzify: Vec::new(),
+        defuzzify: Vec::new(),
+        ruleblocks: Vec::new(),
+        span,
+    };
+
+    for block in inner {
+        let block = next(&mut block.into_inner(), span)?;
+        match block.as_rule() {
+            Rule::var_input => function_block.inputs.extend(declarations(block)?),
+            Rule::var_output => function_block.outputs.extend(declarations(block)?),
+            Rule::fuzzify => function_block.fuzzify.push(fuzzify(block)?),
+            Rule::defuzzify => function_block.defuzzify.push(defuzzify(block)?),
+            Rule::ruleblock => function_block.ruleblocks.push(ruleblock(block)?),
+            _ => return Err(Diagnostic::new("unexpected block", block.as_span())),
         }
-        Rule::var_input => {
-            let mut var_inputs = Vec::new();
-            for vi in pair.into_inner() {
-                var_inputs.push(generate_ast(vi)?);
-            }
-            Ok(AstNode::VarInput(var_inputs))
+    }
+
+    Ok(function_block)
+}
+
+fn declarations(block: Pair<Rule>) -> Result<Vec<Declaration>> {
+    block
+        .into_inner()
+        .map(|declaration| {
+            let span = declaration.as_span();
+            let mut inner = declaration.into_inner();
+            let name = identifier(next(&mut inner, span)?);
+            let var_type = next(&mut inner, span)?;
+            let var_type = VarType {
+                keyword: var_type.as_str(),
+                span: var_type.as_span(),
+            };
+            Ok(Declaration {
+                name,
+                var_type,
+                span,
+            })
+        })
+        .collect()
+}
+
+fn fuzzify(block: Pair<Rule>) -> Result<Fuzzify> {
+    let span = block.as_span();
+    let mut inner = block.into_inner();
+    let mut fuzzify = Fuzzify {
+        variable: identifier(next(&mut inner, span)?),
+        terms: Vec::new(),
+        range: None,
+        span,
+    };
+    for pair in inner {
+        match pair.as_rule() {
+            Rule::range => set_once(&mut fuzzify.range, range(pair)?, "RANGE")?,
+            _ => fuzzify.terms.push(term(pair)?),
         }
-        Rule::var_output => {
-            let mut var_outputs = Vec::new();
-            for vo in pair.into_inner() {
-                var_outputs.push(generate_ast(vo)?);
+    }
+
+    Ok(fuzzify)
+}
+
+fn defuzzify(block: Pair<Rule>) -> Result<Defuzzify> {
+    let span = block.as_span();
+    let mut inner = block.into_inner();
+    let mut defuzzify = Defuzzify {
+        variable: identifier(next(&mut inner, span)?),
+        terms: Vec::new(),
+        method: None,
+        default: None,
+        range: None,
+        span,
+    };
+    for pair in inner {
+        let span = pair.as_span();
+        match pair.as_rule() {
+            Rule::method => {
+                let method = Method {
+                    function: function_op(next(&mut pair.into_inner(), span)?)?,
+                    span,
+                };
+                set_once(&mut defuzzify.method, method, "METHOD")?;
             }
-            Ok(AstNode::VarOutput(var_outputs))
-        }
-        Rule::block | Rule::declaration | Rule::fuzzify | Rule::defuzzify => {
-            generate_ast(pair.into_inner().next().unwrap())
+            Rule::default_value => {
+                let value = next(&mut pair.into_inner(), span)?;
+                let kind = match value.as_rule() {
+                    Rule::no_change => DefaultKind::NoChange,
+                    _ => DefaultKind::Value(number(value)?),
+                };
+                set_once(
+                    &mut defuzzify.default,
+                    DefaultValue { kind, span },
+                    "DEFAULT",
+                )?;
+            }
+            Rule::range => set_once(&mut defuzzify.range, range(pair)?, "RANGE")?,
+            _ => defuzzify.terms.push(term(pair)?),
         }
-        Rule::ruleblock => {
-            println!("Ruleblock called : {:#?}", pair.as_str());
-            let mut rules = Vec::new();
-            for r in pair.into_inner() {
-                rules.push(generate_ast(r)?);
+    }
+
+    Ok(defuzzify)
+}
+
+/// Sets a property that may only be given once in a block
+fn set_once<'i, T: Spanned<'i>>(slot: &mut Option<T>, value: T, keyword: &str) -> Result<()> {
+    if slot.is_some() {
+        return Err(Diagnostic::new(
+            format!("{keyword} is given twice"),
+            value.span(),
+        ));
+    }
+    *slot = Some(value);
+    Ok(())
+}
+
+/// Nodes that can be given only once in a block
+trait Spanned<'i> {
+    fn span(&self) -> Span<'i>;
+}
+
+impl<'i> Spanned<'i> for Method<'i> {
+    fn span(&self) -> Span<'i> {
+        self.span
+    }
+}
+
+impl<'i> Spanned<'i> for DefaultValue<'i> {
+    fn span(&self) -> Span<'i> {
+        self.span
+    }
+}
+
+impl<'i> Spanned<'i> for ast::Range<'i> {
+    fn span(&self) -> Span<'i> {
+        self.span
+    }
+}
+
+fn range(pair: Pair<Rule>) -> Result<ast::Range> {
+    let span = pair.as_span();
+    let mut numbers = pair.into_inner();
+    Ok(ast::Range {
+        min: number(next(&mut numbers, span)?)?,
+        max: number(next(&mut numbers, span)?)?,
+        span,
+    })
+}
+
+fn term(pair: Pair<Rule>) -> Result<Term> {
+    let span = pair.as_span();
+    let mut inner = pair.into_inner();
+    let name = identifier(next(&mut inner, span)?);
+    let mut points = Vec::new();
+    let mut singleton = None;
+    for pair in inner {
+        let span = pair.as_span();
+        let mut numbers = pair.clone().into_inner();
+        match pair.as_rule() {
+            Rule::singleton => {
+                singleton = Some((number(next(&mut numbers, span)?)?, span))
             }
-            Ok(AstNode::Consequent(rules))
+            _ => points.push(Point {
+                x: number(next(&mut numbers, span)?)?,
+                y: number(next(&mut numbers, span)?)?,
+                span,
+            }),
         }
-        Rule::operators => {
-            let inner = pair.into_inner();
-            Ok(AstNode::Operator { name: inner.as_str().into() })
+    }
+
+    Ok(Term {
+        name,
+        shape: match singleton {
+            Some((value, span)) => TermShape::Singleton { value, span },
+            None => TermShape::Points(points),
+        },
+        span,
+    })
+}
+
+fn number(pair: Pair<Rule>) -> Result<f64> {
+    pair.as_str()
+        .parse()
+        .map_err(|_| Diagnostic::new("invalid number", pair.as_span()))
+}
+
+fn function_op(pair: Pair<Rule>) -> Result<FunctionOp> {
+    Ok(match pair.as_str() {
+        "MIN" => FunctionOp::Min,
+        "PROD" => FunctionOp::Prod,
+        "BDIF" => FunctionOp::BDif,
+        "DPROD" => FunctionOp::DProd,
+        "EPROD" => FunctionOp::EProd,
+        "HPROD" => FunctionOp::HProd,
+        "NMIN" => FunctionOp::NMin,
+        "MAX" => FunctionOp::Max,
+        "ASUM" => FunctionOp::ASum,
+        "BSUM" => FunctionOp::BSum,
+        "NSUM" => FunctionOp::NSum,
+        "DSUM" => FunctionOp::DSum,
+        "ESUM" => FunctionOp::ESum,
+        "HSUM" => FunctionOp::HSum,
+        "NMAX" => FunctionOp::NMax,
+        "COG" => FunctionOp::Cog,
+        "COGS" => FunctionOp::Cogs,
+        "COA" => FunctionOp::Coa,
+        "MOM" => FunctionOp::Mom,
+        "LM" => FunctionOp::Lm,
+        "RM" => FunctionOp::Rm,
+        "WSUM" => FunctionOp::WSum,
+        function => {
+            return Err(Diagnostic::new(
+                format!("unknown function `{function}`"),
+                pair.as_span(),
+            ))
         }
-        Rule::antecedent => {
-            println!("Antecedent called : {:#?}", pair.as_str());
-            let mut rules = Vec::new();
-            for r in pair.into_inner() {
-                rules.push(generate_ast(r)?);
+    })
+}
+
+fn ruleblock(block: Pair<Rule>) -> Result<RuleBlock> {
+    let span = block.as_span();
+    let mut inner = block.into_inner();
+    let mut ruleblock = RuleBlock {
+        name: identifier(next(&mut inner, span)?),
+        operators: Vec::new(),
+        rules: Vec::new(),
+        span,
+    };
+
+    for pair in inner {
+        match pair.as_rule() {
+            Rule::operators => {
+                for operator in pair.into_inner() {
+                    let span = operator.as_span();
+                    let kind = match operator.as_rule() {
+                        Rule::and_op => OperatorKind::And,
+                        Rule::or_op => OperatorKind::Or,
+                        Rule::act_op => OperatorKind::Act,
+                        _ => OperatorKind::Accu,
+                    };
+                    if ruleblock.operator(kind).is_some() {
+                        return Err(Diagnostic::new(
+                            format!("{} is given twice", kind.keyword()),
+                            span,
+                        ));
+                    }
+                    ruleblock.operators.push(Operator {
+                        kind,
+                        function: function_op(next(&mut operator.into_inner(), span)?)?,
+                        span,
+                    });
+                }
             }
-            Ok(AstNode::Antecedent(rules))
+            _ => ruleblock.rules.push(rule(pair)?),
         }
-        Rule::consequent => {
-            println!("Consequent called : {:#?}", pair.as_str());
-            let mut rules = Vec::new();
-            for r in pair.into_inner() {
-                rules.push(generate_ast(r)?);
+    }
+
+    Ok(ruleblock)
+}
+
+fn rule(pair: Pair<Rule>) -> Result<RuleStatement> {
+    let span = pair.as_span();
+    let mut inner = pair.into_inner();
+    let number = next(&mut inner, span)?;
+    let number = number
+        .as_str()
+        .parse()
+        .map_err(|_| Diagnostic::new("rule numbers must be positive integers", number.as_span()))?;
+    let condition = condition(next(&mut inner, span)?)?;
+    let mut conclusions = Vec::new();
+    let mut weight = None;
+    for pair in inner {
+        let span = pair.as_span();
+        let mut inner = pair.clone().into_inner();
+        match pair.as_rule() {
+            Rule::weight => {
+                weight = Some(Weight {
+                    value: self::number(next(&mut inner, span)?)?,
+                    span,
+                })
             }
-            Ok(AstNode::Consequent(rules))
+            _ => conclusions.push(Conclusion {
+                variable: identifier(next(&mut inner, span)?),
+                term: identifier(next(&mut inner, span)?),
+                span,
+            }),
+        }
+    }
+
+    Ok(RuleStatement {
+        number,
+        condition,
+        conclusions,
+        weight,
+        span,
+    })
+}
+
+fn condition(antecedent: Pair<Rule>) -> Result<RuleExpr> {
+    fold_condition(antecedent, subcondition)
+}
+
+/// Builds the condition tree of an antecedent made of subconditions separated by `AND` and
+/// `OR` operators, `AND` binding tighter than `OR`. Shared by the FCL and FLL front ends
+fn fold_condition<'i, R: RuleType>(
+    antecedent: Pair<'i, R>,
+    subcondition: impl Fn(Pair<'i, R>) -> Result<RuleExpr<'i>>,
+) -> Result<RuleExpr<'i>> {
+    let span = antecedent.as_span();
+    let mut inner = antecedent.into_inner();
+    let mut disjunction = None;
+    let mut conjunction = subcondition(next(&mut inner, span)?)?;
+    while let Some(op) = inner.next() {
+        let right = subcondition(next(&mut inner, span)?)?;
+        if op.as_str().eq_ignore_ascii_case("AND") {
+            conjunction = combine(conjunction, right, true);
+        } else {
+            disjunction = Some(match disjunction {
+                Some(left) => combine(left, conjunction, false),
+                None => conjunction,
+            });
+            conjunction = right;
         }
-        Rule::rule => {
-            dbg!("Rule called : {:#?}", pair.as_str());
+    }
+
+    Ok(match disjunction {
+        Some(left) => combine(left, conjunction, false),
+        None => conjunction,
+    })
+}
+
+fn combine<'i>(left: RuleExpr<'i>, right: RuleExpr<'i>, and: bool) -> RuleExpr<'i> {
+    let span = left.span().start_pos().span(&right.span().end_pos());
+    let (left, right) = (Box::new(left), Box::new(right));
+    if and {
+        RuleExpr::And { left, right, span }
+    } else {
+        RuleExpr::Or { left, right, span }
+    }
+}
+
+/// `variable IS [NOT] [hedges] term`, `NOT (condition)`, `hedge (condition)` or `(condition)`
+fn subcondition(pair: Pair<Rule>) -> Result<RuleExpr> {
+    let span = pair.as_span();
+    match pair.as_rule() {
+        Rule::antecedent => condition(pair),
+        Rule::negation => Ok(RuleExpr::Not {
+            operand: Box::new(subcondition(next(&mut pair.into_inner(), span)?)?),
+            span,
+        }),
+        Rule::hedged => {
             let mut inner = pair.into_inner();
-            let antecedent = inner.next().unwrap();
-            let consequent = inner.next().unwrap();
-            Ok(AstNode::Rule {
-                antecedent: Box::new(generate_ast(antecedent)?),
-                consequent: Box::new(generate_ast(consequent)?),
+            let hedge = identifier(next(&mut inner, span)?);
+            Ok(RuleExpr::Hedged {
+                hedge,
+                operand: Box::new(subcondition(next(&mut inner, span)?)?),
+                span,
             })
         }
-        Rule::identifier => Ok(AstNode::Identifier {
-            name: pair.as_str().to_string(),
-        }),
-        Rule::var_type => {
-            let var_type = match pair.as_str() {
-                "REAL" => VarType::Number,
-                "BOOL" => VarType::Boolean,
-                _ => unimplemented!("VarType not implemented: {:#?}", pair.as_str()),
+        _ => {
+            let mut inner = pair.into_inner();
+            let variable = identifier(next(&mut inner, span)?);
+            let mut term = next(&mut inner, span)?;
+            let negated = term.as_rule() == Rule::not;
+            if negated {
+                term = next(&mut inner, span)?;
+            }
+            let mut hedges = Vec::new();
+            while term.as_rule() == Rule::hedge {
+                hedges.push(identifier(next(&mut term.into_inner(), span)?));
+                term = next(&mut inner, span)?;
+            }
+            let mut expression = RuleExpr::Is {
+                variable,
+                term: identifier(term),
+                span,
             };
-            Ok(AstNode::VarType(var_type))
-        }
-        Rule::term => {
-            let inner = pair.clone().into_inner();
-            let mut envelope = Vec::new();
-            for t in inner {
-                let mut t_inner = t.into_inner();
-                let v1 = t_inner.next().unwrap();
-                let v2 = t_inner.next().unwrap();
-                envelope.push(v1.as_str().parse()?..v2.as_str().parse()?);
+            // The hedge nearest to the term applies first
+            for hedge in hedges.into_iter().rev() {
+                expression = RuleExpr::Hedged {
+                    hedge,
+                    operand: Box::new(expression),
+                    span,
+                };
             }
-            Ok(AstNode::Term {
-                name: pair.as_str().to_string(),
-                envelope,
+            Ok(if negated {
+                RuleExpr::Not {
+                    operand: Box::new(expression),
+                    span,
+                }
+            } else {
+                expression
             })
         }
-        Rule::number => {
-            let number: f64 = pair.as_str().parse()?;
-            Ok(AstNode::Number(number))
-        }
-        Rule::EOI => Ok(AstNode::EOI),
-        _ => unimplemented!("Rule not implemented: {:#?}", pair.as_rule()),
     }
 }
 
@@ -158,7 +471,6 @@ mod tests {
     use super::*;
 
     #[test]
-    #[ignore]
     fn test_generate_ast() {
         let input = r#"
             FUNCTION_BLOCK RoomTempController 
@@ -210,20 +522,131 @@ mod tests {
 
             END_FUNCTION_BLOCK
         "#;
-        let mut pairs = super::fcl_parser(input).unwrap();
-        for pair in pairs.clone() {
-            let ast = super::generate_ast(pair).unwrap();
-            println!("{:#?}", ast);
-        }
-        let ast = generate_ast(pairs.next().unwrap()).expect("Failed to generate ast");
-        assert!(matches!(ast, AstNode::FunctionBlock(_)));
+        let pairs = super::fcl_parser(input).unwrap();
+        let function_blocks = generate_ast(pairs.peek().unwrap()).expect("Failed to generate ast");
+        assert_eq!(function_blocks.len(), 1);
+
+        let function_block = &function_blocks[0];
+        assert_eq!(function_block.name.name, "RoomTempController");
+        assert_eq!(function_block.inputs.len(), 2);
+        assert_eq!(function_block.inputs[1].var_type.keyword, "REAL");
+        assert_eq!(function_block.outputs[0].name.name, "heating");
+        assert_eq!(function_block.fuzzify[0].terms[0].name.name, "cold");
+        assert_eq!(
+            function_block.defuzzify[0].method.unwrap().function,
+            FunctionOp::Cog
+        );
+
+        let ruleblock = &function_block.ruleblocks[0];
+        assert_eq!(ruleblock.name.name, "No1");
+        assert_eq!(
+            ruleblock.operator(OperatorKind::And).unwrap().function,
+            FunctionOp::Min
+        );
+        assert_eq!(
+            ruleblock.operator(OperatorKind::Accu).unwrap().function,
+            FunctionOp::Max
+        );
+        assert!(ruleblock.operator(OperatorKind::Or).is_none());
+        assert_eq!(ruleblock.rules.len(), 9);
+        assert_eq!(ruleblock.rules[8].number, 9);
+
+        // cold AND high OR medium is parsed as (cold AND high) OR medium
+        let rule = &ruleblock.rules[2];
+        let RuleExpr::Or { left, right, span } = &rule.condition else {
+            panic!("Expected a disjunction, got {:?}", rule.condition);
+        };
+        assert!(matches!(**left, RuleExpr::And { .. }));
+        assert!(matches!(**right, RuleExpr::Is { term, .. } if term.name == "medium"));
+        assert_eq!(
+            span.as_str(),
+            "temperature IS cold AND humidity IS high OR humidity IS medium"
+        );
+        assert_eq!(rule.conclusions[0].term.span.start_pos().line_col().0, 39);
     }
-}
 
-// #[derive(Parser)]
-// #[grammar = "fuzzy_logic/grammar/fll.pest"]
-// pub struct FLLParser;
+    #[test]
+    fn test_iec_constructs() {
+        let input = r#"
+            FUNCTION_BLOCK tipper
+            VAR_INPUT service : REAL; food : REAL; END_VAR
+            VAR_OUTPUT tip : REAL; rating : REAL; END_VAR
+            FUZZIFY service
+              RANGE := (0 .. 10);
+              TERM poor := (0, 1) (4, 0);
+              TERM good := (1, 0) (4, 1) (6, 1) (9, 0);
+            END_FUZZIFY
+            FUZZIFY food
+              TERM rancid := (0, 1) (1, 1) (3, 0);
+            END_FUZZIFY
+            DEFUZZIFY tip
+              TERM cheap := 5;
+              TERM generous := 2.5e1;
+              METHOD : COGS;
+              DEFAULT := 0;
+            END_DEFUZZIFY
+            DEFUZZIFY rating
+              TERM low := (0, 1) (1, 0);
+              METHOD : MOM;
+              DEFAULT := NC;
+            END_DEFUZZIFY
+            RULEBLOCK first
+              AND : PROD;
+              ACT : MIN;
+              ACCU : BSUM;
+              RULE 1 : IF service IS NOT poor AND (food IS rancid OR NOT (service IS good))
+                       THEN tip IS cheap, rating IS low WITH 0.5;
+            END_RULEBLOCK
+            END_FUNCTION_BLOCK
+        "#;
+        let function_blocks = parse_fcl(input).expect("Failed to generate ast");
+        let function_block = &function_blocks[0];
+        assert_eq!(function_block.outputs.len(), 2);
+
+        let range = function_block.fuzzify[0].range.unwrap();
+        assert_eq!((range.min, range.max), (0.0, 10.0));
+
+        let tip = &function_block.defuzzify[0];
+        assert!(matches!(tip.terms[1].shape, TermShape::Singleton { value, .. } if value == 25.0));
+        assert_eq!(tip.method.unwrap().function, FunctionOp::Cogs);
+        assert_eq!(tip.default.unwrap().kind, DefaultKind::Value(0.0));
+        let rating = &function_block.defuzzify[1];
+        assert_eq!(rating.default.unwrap().kind, DefaultKind::NoChange);
 
-// pub fn fll_parser(input: &str) -> Result<(), pest::error::Error<Rule>> {
-//     todo!("Fill this function")
-// }
+        let ruleblock = &function_block.ruleblocks[0];
+        assert_eq!(
+            ruleblock.operator(OperatorKind::Act).unwrap().function,
+            FunctionOp::Min
+        );
+        assert_eq!(
+            ruleblock.operator(OperatorKind::Accu).unwrap().function,
+            FunctionOp::BSum
+        );
+
+        let rule = &ruleblock.rules[0];
+        assert_eq!(rule.weight.unwrap().value, 0.5);
+        assert_eq!(rule.conclusions.len(), 2);
+        let RuleExpr::And { left, right, .. } = &rule.condition else {
+            panic!("Expected a conjunction, got {:?}", rule.condition);
+        };
+        assert!(
+            matches!(&**left, RuleExpr::Not { operand, .. } if matches!(**operand, RuleExpr::Is { .. }))
+        );
+        let RuleExpr::Or { right, .. } = &**right else {
+            panic!("Expected a disjunction, got {right:?}");
+        };
+        assert_eq!(right.span().as_str(), "NOT (service IS good)");
+    }
+
+    #[test]
+    fn test_term_points() {
+        let pairs = FCLParser::parse(Rule::term, "TERM cold := (-20, 1) (-10, 1) (0, 0);").unwrap();
+        let term = term(pairs.peek().unwrap()).unwrap();
+        assert_eq!(term.name.name, "cold");
+        let TermShape::Points(points) = term.shape else {
+            panic!("Expected points, got {:?}", term.shape);
+        };
+        let points: Vec<_> = points.iter().map(|point| (point.x, point.y)).collect();
+        assert_eq!(points, vec![(-20.0, 1.0), (-10.0, 1.0), (0.0, 0.0)]);
+    }
+}
@@ -0,0 +1,440 @@
+//! Typed Abstract Syntax Tree of an FCL program.
+//!
+//! Every node keeps the [`Span`] of the source it was built from so tools can point back to the
+//! exact location of a construct.
+use pest::Span;
+
+/// `FUNCTION_BLOCK name ... END_FUNCTION_BLOCK`
+#[derive(Debug, Clone)]
+pub struct FunctionBlock<'i> {
+    pub name: Identifier<'i>,
+    pub inputs: Vec<Declaration<'i>>,
+    pub outputs: Vec<Declaration<'i>>,
+    pub fuzzify: Vec<Fuzzify<'i>>,
+    pub defuzzify: Vec<Defuzzify<'i>>,
+    pub ruleblocks: Vec<RuleBlock<'i>>,
+    pub span: Span<'i>,
+}
+
+/// Name of a function block, variable, term or rule block
+#[derive(Debug, Clone, Copy)]
+pub struct Identifier<'i> {
+    pub name: &'i str,
+    pub span: Span<'i>,
+}
+
+/// Type of a declared variable, its keyword as written, `REAL`, `INT`, `BOOL`, ...
+#[derive(Debug, Clone, Copy)]
+pub struct VarType<'i> {
+    pub keyword: &'i str,
+    pub span: Span<'i>,
+}
+
+impl VarType<'_> {
+    /// Whether the variable is declared `BOOL` rather than as a number
+    pub fn is_boolean(&self) -> bool {
+        self.keyword == "BOOL"
+    }
+}
+
+/// `name: TYPE;` inside a VAR_INPUT or VAR_OUTPUT block
+#[derive(Debug, Clone)]
+pub struct Declaration<'i> {
+    pub name: Identifier<'i>,
+    pub var_type: VarType<'i>,
+    pub span: Span<'i>,
+}
+
+/// `FUZZIFY variable ... END_FUZZIFY`
+#[derive(Debug, Clone)]
+pub struct Fuzzify<'i> {
+    pub variable: Identifier<'i>,
+    pub terms: Vec<Term<'i>>,
+    pub range: Option<Range<'i>>,
+    pub span: Span<'i>,
+}
+
+/// `DEFUZZIFY variable ... END_DEFUZZIFY`
+#[derive(Debug, Clone)]
+pub struct Defuzzify<'i> {
+    pub variable: Identifier<'i>,
+    pub terms: Vec<Term<'i>>,
+    pub method: Option<Method<'i>>,
+    pub default: Option<DefaultValue<'i>>,
+    pub range: Option<Range<'i>>,
+    pub span: Span<'i>,
+}
+
+/// `RANGE := (min .. max);`
+#[derive(Debug, Clone, Copy)]
+pub struct Range<'i> {
+    pub min: f64,
+    pub max: f64,
+    pub span: Span<'i>,
+}
+
+/// Value of an output when no rule fires
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DefaultKind {
+    Value(f64),
+    /// `NC`, the output keeps its previous value
+    NoChange,
+}
+
+/// `DEFAULT := value;` or `DEFAULT := NC;`
+#[derive(Debug, Clone, Copy)]
+pub struct DefaultValue<'i> {
+    pub kind: DefaultKind,
+    pub span: Span<'i>,
+}
+
+/// `TERM name := shape;`
+#[derive(Debug, Clone)]
+pub struct Term<'i> {
+    pub name: Identifier<'i>,
+    pub shape: TermShape<'i>,
+    pub span: Span<'i>,
+}
+
+/// Shape of the membership function of a term
+#[derive(Debug, Clone)]
+pub enum TermShape<'i> {
+    /// Piecewise linear function given by its points `(x, y)`
+    Points(Vec<Point<'i>>),
+    /// Single value with a membership of 1, used by Sugeno outputs
+    Singleton { value: f64, span: Span<'i> },
+}
+
+impl TermShape<'_> {
+    /// Points of the piecewise linear membership function of the term, a singleton being a
+    /// vertical step up to 1 and back down to 0
+    pub fn points(&self) -> Vec<(f64, f64)> {
+        match self {
+            TermShape::Points(points) => points.iter().map(|point| (point.x, point.y)).collect(),
+            &TermShape::Singleton { value, .. } => vec![(value, 0.0), (value, 1.0), (value, 0.0)],
+        }
+    }
+}
+
+/// `(x, y)` point of a term
+#[derive(Debug, Clone, Copy)]
+pub struct Point<'i> {
+    pub x: f64,
+    pub y: f64,
+    pub span: Span<'i>,
+}
+
+/// Functions that can be named by operators and methods
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FunctionOp {
+    /// Minimum
+    Min,
+    /// Algebraic product
+    Prod,
+    /// Bounded difference
+    BDif,
+    /// Drastic product
+    DProd,
+    /// Einstein product
+    EProd,
+    /// Hamacher product
+    HProd,
+    /// Nilpotent minimum
+    NMin,
+    /// Maximum
+    Max,
+    /// Algebraic sum
+    ASum,
+    /// Bounded sum
+    BSum,
+    /// Normalised sum
+    NSum,
+    /// Drastic sum
+    DSum,
+    /// Einstein sum
+    ESum,
+    /// Hamacher sum
+    HSum,
+    /// Nilpotent maximum
+    NMax,
+    /// Center of gravity
+    Cog,
+    /// Center of gravity for singletons
+    Cogs,
+    /// Center of area
+    Coa,
+    /// Mean of maximum
+    Mom,
+    /// Left most maximum
+    Lm,
+    /// Right most maximum
+    Rm,
+    /// Weighted sum
+    WSum,
+}
+
+impl FunctionOp {
+    /// Keyword of the function in FCL
+    pub fn keyword(self) -> &'static str {
+        match self {
+            FunctionOp::Min => "MIN",
+            FunctionOp::Prod => "PROD",
+            FunctionOp::BDif => "BDIF",
+            FunctionOp::DProd => "DPROD",
+            FunctionOp::EProd => "EPROD",
+            FunctionOp::HProd => "HPROD",
+            FunctionOp::NMin => "NMIN",
+            FunctionOp::Max => "MAX",
+            FunctionOp::ASum => "ASUM",
+            FunctionOp::BSum => "BSUM",
+            FunctionOp::NSum => "NSUM",
+            FunctionOp::DSum => "DSUM",
+            FunctionOp::ESum => "ESUM",
+            FunctionOp::HSum => "HSUM",
+            FunctionOp::NMax => "NMAX",
+            FunctionOp::Cog => "COG",
+            FunctionOp::Cogs => "COGS",
+            FunctionOp::Coa => "COA",
+            FunctionOp::Mom => "MOM",
+            FunctionOp::Lm => "LM",
+            FunctionOp::Rm => "RM",
+            FunctionOp::WSum => "WSUM",
+        }
+    }
+
+    /// fuzzylite name of the norm computed by the function, see [`crate::fuzzy_logic::norms`]
+    pub fn norm(self) -> Option<&'static str> {
+        Some(match self {
+            FunctionOp::Min => "Minimum",
+            FunctionOp::Prod => "AlgebraicProduct",
+            FunctionOp::BDif => "BoundedDifference",
+            FunctionOp::DProd => "DrasticProduct",
+            FunctionOp::EProd => "EinsteinProduct",
+            FunctionOp::HProd => "HamacherProduct",
+            FunctionOp::NMin => "NilpotentMinimum",
+            FunctionOp::Max => "Maximum",
+            FunctionOp::ASum => "AlgebraicSum",
+            FunctionOp::BSum => "BoundedSum",
+            FunctionOp::NSum => "NormalizedSum",
+            FunctionOp::DSum => "DrasticSum",
+            FunctionOp::ESum => "EinsteinSum",
+            FunctionOp::HSum => "HamacherSum",
+            FunctionOp::NMax => "NilpotentMaximum",
+            _ => return None,
+        })
+    }
+
+    /// Function computing the norm named `name` in fuzzylite
+    pub fn from_norm(name: &str) -> Option<FunctionOp> {
+        OperatorKind::And
+            .functions()
+            .iter()
+            .chain(OperatorKind::Or.functions())
+            .copied()
+            .find(|function| function.norm() == Some(name))
+    }
+
+    /// fuzzylite name of the defuzzifier of the method, see [`crate::fuzzy_logic::defuzzifier`]
+    pub fn defuzzifier(self) -> Option<&'static str> {
+        Some(match self {
+            FunctionOp::Cog => "Centroid",
+            FunctionOp::Cogs => "WeightedAverage",
+            FunctionOp::Coa => "Bisector",
+            FunctionOp::Mom => "MeanOfMaximum",
+            FunctionOp::Lm => "SmallestOfMaximum",
+            FunctionOp::Rm => "LargestOfMaximum",
+            FunctionOp::WSum => "WeightedSum",
+            _ => return None,
+        })
+    }
+
+    /// Method computing the defuzzifier named `name` in fuzzylite
+    pub fn from_defuzzifier(name: &str) -> Option<FunctionOp> {
+        [
+            FunctionOp::Cog,
+            FunctionOp::Cogs,
+            FunctionOp::Coa,
+            FunctionOp::Mom,
+            FunctionOp::Lm,
+            FunctionOp::Rm,
+            FunctionOp::WSum,
+        ]
+        .into_iter()
+        .find(|function| function.defuzzifier() == Some(name))
+    }
+
+    /// Whether the function is a defuzzification method
+    pub fn is_method(self) -> bool {
+        matches!(
+            self,
+            FunctionOp::Cog
+                | FunctionOp::Cogs
+                | FunctionOp::Coa
+                | FunctionOp::Mom
+                | FunctionOp::Lm
+                | FunctionOp::Rm
+                | FunctionOp::WSum
+        )
+    }
+}
+
+/// `METHOD: function;`
+#[derive(Debug, Clone, Copy)]
+pub struct Method<'i> {
+    pub function: FunctionOp,
+    pub span: Span<'i>,
+}
+
+/// Operator configured by a rule block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperatorKind {
+    And,
+    Or,
+    /// Activation, how the firing strength of a rule is applied to its conclusion
+    Act,
+    /// Accumulation of the conclusions of the rules
+    Accu,
+}
+
+impl OperatorKind {
+    /// Keyword of the operator in FCL
+    pub fn keyword(self) -> &'static str {
+        match self {
+            OperatorKind::And => "AND",
+            OperatorKind::Or => "OR",
+            OperatorKind::Act => "ACT",
+            OperatorKind::Accu => "ACCU",
+        }
+    }
+
+    /// Functions the standard allows for the operator
+    pub fn functions(self) -> &'static [FunctionOp] {
+        match self {
+            OperatorKind::And | OperatorKind::Act => &[
+                FunctionOp::Min,
+                FunctionOp::Prod,
+                FunctionOp::BDif,
+                FunctionOp::DProd,
+                FunctionOp::EProd,
+                FunctionOp::HProd,
+                FunctionOp::NMin,
+            ],
+            OperatorKind::Or | OperatorKind::Accu => &[
+                FunctionOp::Max,
+                FunctionOp::ASum,
+                FunctionOp::BSum,
+                FunctionOp::NSum,
+                FunctionOp::DSum,
+                FunctionOp::ESum,
+                FunctionOp::HSum,
+                FunctionOp::NMax,
+            ],
+        }
+    }
+}
+
+/// `AND: function;`, `OR: function;`, `ACT: function;` or `ACCU: function;`
+#[derive(Debug, Clone, Copy)]
+pub struct Operator<'i> {
+    pub kind: OperatorKind,
+    pub function: FunctionOp,
+    pub span: Span<'i>,
+}
+
+/// `RULEBLOCK name ... END_RULEBLOCK`
+#[derive(Debug, Clone)]
+pub struct RuleBlock<'i> {
+    pub name: Identifier<'i>,
+    pub operators: Vec<Operator<'i>>,
+    pub rules: Vec<RuleStatement<'i>>,
+    pub span: Span<'i>,
+}
+
+impl<'i> RuleBlock<'i> {
+    /// Operator configured for `kind`, if any
+    pub fn operator(&self, kind: OperatorKind) -> Option<&Operator<'i>> {
+        self.operators.iter().find(|operator| operator.kind == kind)
+    }
+}
+
+/// `RULE number : IF condition THEN conclusions WITH weight;`
+#[derive(Debug, Clone)]
+pub struct RuleStatement<'i> {
+    pub number: u32,
+    pub condition: RuleExpr<'i>,
+    pub conclusions: Vec<Conclusion<'i>>,
+    pub weight: Option<Weight<'i>>,
+    pub span: Span<'i>,
+}
+
+/// `WITH weight` of a rule
+#[derive(Debug, Clone, Copy)]
+pub struct Weight<'i> {
+    pub value: f64,
+    pub span: Span<'i>,
+}
+
+/// Condition of a rule, `AND` binds tighter than `OR`.
+/// `variable IS NOT term` is represented as the negation of `variable IS term`, and the FLL
+/// `variable is very term` as `variable is term` modified by the hedge
+#[derive(Debug, Clone)]
+pub enum RuleExpr<'i> {
+    /// `variable IS term`
+    Is {
+        variable: Identifier<'i>,
+        term: Identifier<'i>,
+        span: Span<'i>,
+    },
+    And {
+        left: Box<RuleExpr<'i>>,
+        right: Box<RuleExpr<'i>>,
+        span: Span<'i>,
+    },
+    Or {
+        left: Box<RuleExpr<'i>>,
+        right: Box<RuleExpr<'i>>,
+        span: Span<'i>,
+    },
+    Not {
+        operand: Box<RuleExpr<'i>>,
+        span: Span<'i>,
+    },
+    Hedged {
+        hedge: Identifier<'i>,
+        operand: Box<RuleExpr<'i>>,
+        span: Span<'i>,
+    },
+}
+
+impl<'i> RuleExpr<'i> {
+    pub fn span(&self) -> Span<'i> {
+        match self {
+            RuleExpr::Is { span, .. }
+            | RuleExpr::And { span, .. }
+            | RuleExpr::Or { span, .. }
+            | RuleExpr::Not { span, .. }
+            | RuleExpr::Hedged { span, .. } => *span,
+        }
+    }
+
+    /// Every `variable IS term` test of the condition, from left to right
+    pub fn tests(&self) -> Vec<(&Identifier<'i>, &Identifier<'i>)> {
+        match self {
+            RuleExpr::Is { variable, term, .. } => vec![(variable, term)],
+            RuleExpr::And { left, right, .. } | RuleExpr::Or { left, right, .. } => {
+                let mut tests = left.tests();
+                tests.extend(right.tests());
+                tests
+            }
+            RuleExpr::Not { operand, .. } | RuleExpr::Hedged { operand, .. } => operand.tests(),
+        }
+    }
+}
+
+/// `variable IS term` after the `THEN` of a rule
+#[derive(Debug, Clone, Copy)]
+pub struct Conclusion<'i> {
+    pub variable: Identifier<'i>,
+    pub term: Identifier<'i>,
+    pub span: Span<'i>,
+}
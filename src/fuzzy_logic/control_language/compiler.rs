@@ -0,0 +1,1180 @@
+//! Compiler turning an FCL program or an FLL engine into a runnable fuzzy inference system
+use std::rc::Rc;
+
+use anyhow::{anyhow, bail, Context, Result};
+use pest::Span;
+
+use super::ast::{self, *};
+use super::diagnostics::{check, Diagnostics};
+use super::fll::{self, parse_fll, Engine};
+use super::parse_fcl;
+use crate::fuzzy_logic::defuzzifier::{defuzzifier, Centroid};
+use crate::fuzzy_logic::inference::{Mamdani, Sugeno};
+use crate::fuzzy_logic::norms::{s_norm, t_norm};
+use crate::fuzzy_logic::{
+    hedges, Activation, Comparison, FuzzySet, FuzzyVariable, MembershipFunction, Rule, RuleBlock,
+    RuleExpr, SNorm,
+};
+
+/// Input variables, output variables and rule blocks of a compiled system
+type Parts = (Vec<FuzzyVariable>, Vec<FuzzyVariable>, Vec<RuleBlock>);
+
+/// Compiles the first function block of an FCL program into a [`Mamdani`] system
+pub fn compile_fcl(input: &str) -> Result<Mamdani> {
+    compile_first(input, compile)
+}
+
+/// Compiles a function block into a [`Mamdani`] system.
+/// Every problem found by [`check`] is returned at once as [`Diagnostics`]
+pub fn compile(function_block: &FunctionBlock) -> Result<Mamdani> {
+    let (inputs, outputs, rule_blocks) = fcl_parts(function_block, |terms| Ok(fuzzy_sets(terms)))?;
+    Ok(Mamdani::with_rule_blocks(inputs, outputs, rule_blocks)?)
+}
+
+/// Compiles the first function block of an FCL program into a [`Sugeno`] system
+pub fn compile_fcl_sugeno(input: &str) -> Result<Sugeno> {
+    compile_first(input, compile_sugeno)
+}
+
+/// Compiles a function block whose output terms are singletons, defuzzified by `COGS` or
+/// `WSUM`, into a zero-order [`Sugeno`] system
+pub fn compile_sugeno(function_block: &FunctionBlock) -> Result<Sugeno> {
+    let (inputs, outputs, rule_blocks) = fcl_parts(function_block, singletons)?;
+    Ok(Sugeno::with_rule_blocks(inputs, outputs, rule_blocks)?)
+}
+
+/// Compiles every function block of an FCL program into a [`Mamdani`] system, by the name of
+/// the block
+pub fn compile_fcl_blocks(input: &str) -> Result<Vec<(String, Mamdani)>> {
+    let function_blocks = parse_fcl(input).map_err(|diagnostic| Diagnostics(vec![diagnostic]))?;
+    if function_blocks.is_empty() {
+        bail!("FCL program has no FUNCTION_BLOCK");
+    }
+    function_blocks
+        .iter()
+        .map(|function_block| {
+            let name = function_block.name.name;
+            let mamdani =
+                compile(function_block).with_context(|| format!("FUNCTION_BLOCK {name}"))?;
+            Ok((name.to_string(), mamdani))
+        })
+        .collect()
+}
+
+fn compile_first<S>(input: &str, compile: fn(&FunctionBlock) -> Result<S>) -> Result<S> {
+    let function_blocks = parse_fcl(input).map_err(|diagnostic| Diagnostics(vec![diagnostic]))?;
+    let function_block = function_blocks
+        .first()
+        .ok_or_else(|| anyhow!("FCL program has no FUNCTION_BLOCK"))?;
+
+    compile(function_block)
+}
+
+/// Variables and rule blocks of a function block, the terms of the outputs being compiled by
+/// `output_sets`
+fn fcl_parts(
+    function_block: &FunctionBlock,
+    output_sets: fn(&[Term]) -> Result<Vec<FuzzySet>>,
+) -> Result<Parts> {
+    let diagnostics = check(function_block);
+    if !diagnostics.is_empty() {
+        return Err(Diagnostics(diagnostics).into());
+    }
+    let name = function_block.name.name;
+
+    let inputs = function_block
+        .inputs
+        .iter()
+        .map(|input| {
+            let fuzzify = function_block
+                .fuzzify
+                .iter()
+                .find(|fuzzify| fuzzify.variable.name == input.name.name)
+                .ok_or_else(|| {
+                    anyhow!("input variable {} has no FUZZIFY block", input.name.name)
+                })?;
+            let variable = FuzzyVariable::new(input.name.name, fuzzy_sets(&fuzzify.terms));
+            Ok(match fuzzify.range {
+                Some(range) => variable.with_range(range.min, range.max),
+                None => variable,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    if function_block.outputs.is_empty() {
+        bail!("FUNCTION_BLOCK {name} declares no output variable");
+    }
+    let outputs = function_block
+        .outputs
+        .iter()
+        .map(|output| {
+            let output = output.name.name;
+            let defuzzify = function_block
+                .defuzzify
+                .iter()
+                .find(|defuzzify| defuzzify.variable.name == output)
+                .ok_or_else(|| anyhow!("output variable {output} has no DEFUZZIFY block"))?;
+            // Without METHOD, the output is defuzzified with the center of gravity
+            let defuzzifier = match defuzzify.method {
+                Some(method) => method
+                    .function
+                    .defuzzifier()
+                    .and_then(|name| defuzzifier(name, None))
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "METHOD {} at {} is not supported",
+                            method.function.keyword(),
+                            location(method.span)
+                        )
+                    })?,
+                None => Rc::new(Centroid::default()),
+            };
+            // Weighted defuzzifiers combine the values of the conclusions, which are singletons
+            // in FCL
+            let sets = match defuzzifier.resolution() {
+                Some(_) => output_sets(&defuzzify.terms)?,
+                None => singletons(&defuzzify.terms)?,
+            };
+            let mut output = FuzzyVariable::new(output, sets).with_defuzzifier(defuzzifier);
+            if let Some(default) = defuzzify.default {
+                output = default_value(output, default);
+            }
+            if let Some(range) = defuzzify.range {
+                output = output.with_range(range.min, range.max);
+            }
+            check_range(&output)?;
+            Ok(output)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let rule_blocks = function_block
+        .ruleblocks
+        .iter()
+        .map(|ruleblock| {
+            let rules = ruleblock
+                .rules
+                .iter()
+                .map(|rule| compile_rule(rule, &inputs, &outputs))
+                .collect::<Result<_>>()?;
+            compile_operators(ruleblock, RuleBlock::new(ruleblock.name.name, rules))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if rule_blocks.iter().all(|block| block.rules.is_empty()) {
+        bail!("FUNCTION_BLOCK {name} has no rules");
+    }
+
+    Ok((inputs, outputs, rule_blocks))
+}
+
+/// Compiles an FLL engine into a [`Mamdani`] system
+pub fn compile_fll(input: &str) -> Result<Mamdani> {
+    let engine = parse_fll(input).map_err(|diagnostic| Diagnostics(vec![diagnostic]))?;
+    compile_engine(&engine)
+}
+
+/// Compiles an FLL engine into a [`Mamdani`] system
+pub fn compile_engine(engine: &Engine) -> Result<Mamdani> {
+    let (inputs, outputs, rule_blocks) = fll_parts(engine)?;
+    Ok(Mamdani::with_rule_blocks(inputs, outputs, rule_blocks)?)
+}
+
+/// Compiles an FLL engine whose output terms are `Constant` or `Linear` into a [`Sugeno`]
+/// system
+pub fn compile_fll_sugeno(input: &str) -> Result<Sugeno> {
+    let engine = parse_fll(input).map_err(|diagnostic| Diagnostics(vec![diagnostic]))?;
+    compile_engine_sugeno(&engine)
+}
+
+/// Compiles an FLL engine into a [`Sugeno`] system, see [`compile_engine`]
+pub fn compile_engine_sugeno(engine: &Engine) -> Result<Sugeno> {
+    let (inputs, outputs, rule_blocks) = fll_parts(engine)?;
+    Ok(Sugeno::with_rule_blocks(inputs, outputs, rule_blocks)?)
+}
+
+fn fll_parts(engine: &Engine) -> Result<Parts> {
+    let name = engine.name.map(|name| name.name).unwrap_or_default();
+
+    let inputs = engine
+        .inputs
+        .iter()
+        .map(|input| {
+            if !input.enabled {
+                bail!(
+                    "input variable {} at {} is disabled, every input must be enabled",
+                    input.name.name,
+                    location(input.span)
+                );
+            }
+            fll_variable(
+                input,
+                FuzzyVariable::new(input.name.name, fll_fuzzy_sets(&input.terms)?),
+            )
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    if engine.outputs.is_empty() {
+        bail!("Engine {name} declares no output variable");
+    }
+    let outputs = engine
+        .outputs
+        .iter()
+        .map(|output| {
+            let mut variable = FuzzyVariable::new(output.name.name, fll_fuzzy_sets(&output.terms)?);
+            if let Some(fll::Defuzzifier {
+                name, resolution, ..
+            }) = output.defuzzifier
+            {
+                let defuzzifier = defuzzifier(name.name, resolution).ok_or_else(|| {
+                    anyhow!(
+                        "defuzzifier {} at {} is not supported",
+                        name.name,
+                        location(name.span)
+                    )
+                })?;
+                variable = variable.with_defuzzifier(defuzzifier);
+            }
+            if let Some(default) = output.default {
+                variable = default_value(variable, default);
+            }
+            if output.lock_previous {
+                variable = variable.with_lock_previous(true);
+            }
+            let variable = fll_variable(output, variable)?;
+            check_range(&variable)?;
+            Ok(variable)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    // fuzzylite aggregates per output variable and the rule blocks here, which requires the
+    // outputs to agree
+    let aggregations = engine
+        .outputs
+        .iter()
+        .map(|output| fll_norm("aggregation", output.aggregation, s_norm))
+        .collect::<Result<Vec<_>>>()?;
+    let aggregation = aggregations[0].clone();
+    let name_of =
+        |norm: &Option<Rc<dyn SNorm>>| norm.as_ref().map_or("Maximum", |norm| norm.name());
+    if aggregations
+        .iter()
+        .any(|other| name_of(other) != name_of(&aggregation))
+    {
+        bail!("the outputs of Engine {name} have different aggregations, which is not supported");
+    }
+
+    let mut rule_blocks = Vec::new();
+    for ruleblock in engine
+        .ruleblocks
+        .iter()
+        .filter(|ruleblock| ruleblock.enabled)
+    {
+        let activation = ruleblock.activation.map(fll_activation).transpose()?;
+        let rules = ruleblock
+            .rules
+            .iter()
+            .map(|rule| compile_rule(rule, &inputs, &outputs))
+            .collect::<Result<_>>()?;
+        let mut block = RuleBlock::new(
+            ruleblock.name.map(|name| name.name).unwrap_or_default(),
+            rules,
+        );
+        if let Some(conjunction) = fll_norm("conjunction", ruleblock.conjunction, t_norm)? {
+            block = block.with_conjunction(conjunction);
+        }
+        if let Some(disjunction) = fll_norm("disjunction", ruleblock.disjunction, s_norm)? {
+            block = block.with_disjunction(disjunction);
+        }
+        if let Some(implication) = fll_norm("implication", ruleblock.implication, t_norm)? {
+            block = block.with_implication(implication);
+        }
+        if let Some(activation) = activation {
+            block = block.with_activation(activation);
+        }
+        if let Some(aggregation) = &aggregation {
+            block = block.with_accumulation(aggregation.clone());
+        }
+        rule_blocks.push(block);
+    }
+    if rule_blocks.iter().all(|block| block.rules.is_empty()) {
+        bail!("Engine {name} has no enabled rules");
+    }
+
+    Ok((inputs, outputs, rule_blocks))
+}
+
+/// Sets the range and its lock of an FLL variable on the compiled `variable`
+fn fll_variable(fll: &fll::Variable, mut variable: FuzzyVariable) -> Result<FuzzyVariable> {
+    if let Some(range) = fll.range {
+        if range.min >= range.max {
+            bail!(
+                "range of {} at {} must have min < max",
+                fll.name.name,
+                location(range.span)
+            );
+        }
+        variable = variable.with_range(range.min, range.max);
+    }
+    Ok(variable.with_lock_range(fll.lock_range))
+}
+
+/// Norm named by `property`, looked up by its fuzzylite name, `none` leaves the default one
+fn fll_norm<N: ?Sized>(
+    property: &str,
+    norm: Option<Identifier>,
+    lookup: fn(&str) -> Option<Rc<N>>,
+) -> Result<Option<Rc<N>>> {
+    match norm {
+        Some(norm) if norm.name != "none" => lookup(norm.name).map(Some).ok_or_else(|| {
+            anyhow!(
+                "{property}: {} at {} is not supported",
+                norm.name,
+                location(norm.span)
+            )
+        }),
+        _ => Ok(None),
+    }
+}
+
+/// Activation method named as in fuzzylite, with its parameters
+fn fll_activation(activation: fll::Activation) -> Result<Activation> {
+    let name = activation.name.name;
+    let invalid = || {
+        anyhow!(
+            "activation {name} {} at {} has invalid parameters",
+            activation.parameters,
+            location(activation.span)
+        )
+    };
+    let parameters: Vec<_> = activation.parameters.split_whitespace().collect();
+    let number = |index: usize, default: f64| match parameters.get(index) {
+        Some(parameter) => parameter.parse::<f64>().map_err(|_| invalid()),
+        None => Ok(default),
+    };
+    let count = |default: usize| match parameters.first() {
+        Some(parameter) => parameter.parse::<usize>().map_err(|_| invalid()),
+        None => Ok(default),
+    };
+    let arity = match name {
+        "General" | "Proportional" => 0,
+        "First" | "Last" | "Threshold" => 2,
+        "Highest" | "Lowest" => 1,
+        _ => bail!(
+            "activation {name} at {} is not supported",
+            location(activation.span)
+        ),
+    };
+    if parameters.len() > arity {
+        return Err(invalid());
+    }
+    Ok(match name {
+        "General" => Activation::General,
+        "Proportional" => Activation::Proportional,
+        "First" => Activation::First {
+            count: count(1)?,
+            threshold: number(1, 0.0)?,
+        },
+        "Last" => Activation::Last {
+            count: count(1)?,
+            threshold: number(1, 0.0)?,
+        },
+        "Highest" => Activation::Highest(count(1)?),
+        "Lowest" => Activation::Lowest(count(1)?),
+        _ => {
+            let comparison = match parameters.first() {
+                Some(operator) => Comparison::from_operator(operator).ok_or_else(invalid)?,
+                None => Comparison::GreaterOrEqual,
+            };
+            Activation::Threshold(comparison, number(1, 0.0)?)
+        }
+    })
+}
+
+/// Output `variable` taking `default` when no rule fires on it
+fn default_value(variable: FuzzyVariable, default: DefaultValue) -> FuzzyVariable {
+    match default.kind {
+        DefaultKind::Value(value) => variable.with_default(value),
+        DefaultKind::NoChange => variable.with_lock_previous(true),
+    }
+}
+
+fn fll_fuzzy_sets(terms: &[fll::Term]) -> Result<Vec<FuzzySet>> {
+    let mut sets: Vec<FuzzySet> = Vec::new();
+    for term in terms {
+        if sets.iter().any(|set| set.name == term.name.name) {
+            bail!(
+                "term {} is defined twice at {}",
+                term.name.name,
+                location(term.name.span)
+            );
+        }
+        sets.push(fll_fuzzy_set(term)?);
+    }
+    Ok(sets)
+}
+
+/// Fuzzy set of a fuzzylite term.
+/// Terms other than `Constant` and `Linear` accept an optional trailing height scaling their
+/// membership
+fn fll_fuzzy_set(term: &fll::Term) -> Result<FuzzySet> {
+    let (kind, name) = (term.kind.name, term.name.name);
+    let count = term.parameters.split_whitespace().count();
+    let arity = match kind {
+        "Constant" => 1,
+        "Linear" => count.max(1),
+        "Ramp" | "Rectangle" | "SShape" | "ZShape" | "Gaussian" | "Sigmoid" | "Cosine"
+        | "Concave" | "Spike" | "Binary" => 2,
+        "Triangle" | "Bell" => 3,
+        "Trapezoid" | "PiShape" | "SigmoidDifference" | "SigmoidProduct" | "GaussianProduct" => 4,
+        "Discrete" => count / 2 * 2,
+        _ => bail!(
+            "term {name} at {} is a {kind}, which is not supported",
+            location(term.span)
+        ),
+    };
+    let heightless = matches!(kind, "Constant" | "Linear");
+    let values = term
+        .values()
+        .filter(|values| values.len() == arity || (!heightless && values.len() == arity + 1))
+        .ok_or_else(|| {
+            anyhow!(
+                "term {name} at {} expects {arity} numbers for a {kind}",
+                location(term.span)
+            )
+        })?;
+    let (p, height) = (&values[..arity], values.get(arity).copied().unwrap_or(1.0));
+    let function = match kind {
+        "Discrete" => {
+            let mut points: Vec<_> = p
+                .chunks(2)
+                .map(|point| (point[0], height * point[1]))
+                .collect();
+            points.sort_by(|a, b| a.0.total_cmp(&b.0));
+            return MembershipFunction::piecewise_linear(&points)
+                .map(|function| FuzzySet::with_function(name, function))
+                .with_context(|| format!("term {name} at {}", location(term.span)));
+        }
+        "Constant" => MembershipFunction::constant(p[0]),
+        "Linear" => MembershipFunction::linear(&p[..arity - 1], p[arity - 1]),
+        "Ramp" => MembershipFunction::ramp(p[0], p[1]),
+        "Rectangle" => MembershipFunction::rectangle(p[0], p[1]),
+        "SShape" => MembershipFunction::s_shape(p[0], p[1]),
+        "ZShape" => MembershipFunction::z_shape(p[0], p[1]),
+        "Gaussian" => MembershipFunction::gaussian(p[0], p[1]),
+        "Sigmoid" => MembershipFunction::sigmoid(p[1], p[0]),
+        "Cosine" => MembershipFunction::cosine(p[0], p[1]),
+        "Concave" => MembershipFunction::concave(p[0], p[1]),
+        "Spike" => MembershipFunction::spike(p[0], p[1]),
+        "Binary" => MembershipFunction::binary(p[0], p[1]),
+        "Triangle" => MembershipFunction::triangular(p[0], p[1], p[2]),
+        "Bell" => MembershipFunction::bell(p[1], p[2], p[0]),
+        "Trapezoid" => MembershipFunction::trapezoidal(p[0], p[1], p[2], p[3]),
+        "PiShape" => MembershipFunction::pi_shape(p[0], p[1], p[2], p[3]),
+        "SigmoidDifference" => MembershipFunction::sigmoid_difference(p[0], p[1], p[2], p[3]),
+        "SigmoidProduct" => MembershipFunction::sigmoid_product(p[0], p[1], p[2], p[3]),
+        _ => MembershipFunction::gaussian_product(p[0], p[1], p[2], p[3]),
+    }
+    .with_context(|| format!("term {name} at {}", location(term.span)))?;
+
+    Ok(FuzzySet::with_function(name, function).with_height(height))
+}
+
+/// Integral defuzzifiers sample the aggregated output over the range of the output variable
+fn check_range(output: &FuzzyVariable) -> Result<()> {
+    match (output.defuzzifier.resolution(), output.range()) {
+        (Some(_), None) => bail!("output variable {} needs a range", output.name),
+        _ => Ok(()),
+    }
+}
+
+/// Position of a span in the source, used in error messages
+fn location(span: Span) -> String {
+    let (line, column) = span.start_pos().line_col();
+    format!("line {line}, column {column}")
+}
+
+fn fuzzy_sets(terms: &[Term]) -> Vec<FuzzySet> {
+    terms
+        .iter()
+        .map(|term| FuzzySet::from_points(term.name.name, term.shape.points()))
+        .collect()
+}
+
+/// Constant sets of singleton terms, the only ones of weighted and Sugeno outputs in FCL
+fn singletons(terms: &[Term]) -> Result<Vec<FuzzySet>> {
+    terms
+        .iter()
+        .map(|term| match term.shape {
+            TermShape::Singleton { value, .. } => Ok(FuzzySet::with_function(
+                term.name.name,
+                MembershipFunction::constant(value)?,
+            )),
+            _ => bail!(
+                "term {} at {} is not a singleton, as the terms of COGS and WSUM outputs must be",
+                term.name.name,
+                location(term.span)
+            ),
+        })
+        .collect()
+}
+
+/// Sets the norms named by the operators of `ruleblock` on `block`
+fn compile_operators(ruleblock: &ast::RuleBlock, mut block: RuleBlock) -> Result<RuleBlock> {
+    for operator in &ruleblock.operators {
+        let unsupported = || {
+            anyhow!(
+                "{}: {} at {} is not supported",
+                operator.kind.keyword(),
+                operator.function.keyword(),
+                location(operator.span)
+            )
+        };
+        let norm = operator.function.norm().ok_or_else(unsupported)?;
+        block = match operator.kind {
+            OperatorKind::And => block.with_conjunction(t_norm(norm).ok_or_else(unsupported)?),
+            OperatorKind::Or => block.with_disjunction(s_norm(norm).ok_or_else(unsupported)?),
+            OperatorKind::Act => block.with_implication(t_norm(norm).ok_or_else(unsupported)?),
+            OperatorKind::Accu => block.with_accumulation(s_norm(norm).ok_or_else(unsupported)?),
+        };
+    }
+    Ok(block)
+}
+
+fn compile_rule(
+    rule: &RuleStatement,
+    inputs: &[FuzzyVariable],
+    outputs: &[FuzzyVariable],
+) -> Result<Rule> {
+    let number = rule.number;
+    let mut consequents = Vec::new();
+    for conclusion in &rule.conclusions {
+        let output = outputs
+            .iter()
+            .find(|output| output.name == conclusion.variable.name)
+            .ok_or_else(|| {
+                anyhow!(
+                    "RULE {number} concludes on {} at {}, which is not an output variable",
+                    conclusion.variable.name,
+                    location(conclusion.variable.span)
+                )
+            })?;
+        find_set(output, &conclusion.term)?;
+        consequents.push((conclusion.variable.name, conclusion.term.name));
+    }
+    let Some((&consequent, others)) = consequents.split_first() else {
+        bail!("RULE {number} at {} has no conclusion", location(rule.span));
+    };
+
+    let condition = compile_condition(number, &rule.condition, inputs)?;
+    let weight = rule.weight.map_or(1.0, |weight| weight.value);
+    let rule = Rule::with_condition(condition, consequent).with_weight(weight);
+    Ok(others
+        .iter()
+        .fold(rule, |rule, &other| rule.with_consequent(other)))
+}
+
+/// Condition of a rule, flattening chains of the same connective
+fn compile_condition(
+    number: u32,
+    condition: &ast::RuleExpr,
+    inputs: &[FuzzyVariable],
+) -> Result<RuleExpr> {
+    let compile = |operand: &ast::RuleExpr| compile_condition(number, operand, inputs);
+    Ok(match condition {
+        ast::RuleExpr::Is { variable, term, .. } => {
+            let input = inputs
+                .iter()
+                .find(|input| input.name == variable.name)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "RULE {number} refers to {} at {}, which is not an input variable",
+                        variable.name,
+                        location(variable.span)
+                    )
+                })?;
+            find_set(input, term)?;
+            RuleExpr::is(variable.name, term.name)
+        }
+        ast::RuleExpr::And { left, right, .. } => {
+            let mut operands = Vec::new();
+            for operand in [compile(left)?, compile(right)?] {
+                match operand {
+                    RuleExpr::And(conjuncts) => operands.extend(conjuncts),
+                    operand => operands.push(operand),
+                }
+            }
+            RuleExpr::And(operands)
+        }
+        ast::RuleExpr::Or { left, right, .. } => {
+            let mut operands = Vec::new();
+            for operand in [compile(left)?, compile(right)?] {
+                match operand {
+                    RuleExpr::Or(disjuncts) => operands.extend(disjuncts),
+                    operand => operands.push(operand),
+                }
+            }
+            RuleExpr::Or(operands)
+        }
+        ast::RuleExpr::Not { operand, .. } => RuleExpr::Not(Box::new(compile(operand)?)),
+        ast::RuleExpr::Hedged { hedge, operand, .. } => {
+            let modifier = hedges::hedge(hedge.name).ok_or_else(|| {
+                anyhow!(
+                    "RULE {number} uses the hedge {} at {}, which is not supported",
+                    hedge.name,
+                    location(hedge.span)
+                )
+            })?;
+            RuleExpr::Hedged(modifier, Box::new(compile(operand)?))
+        }
+    })
+}
+
+fn find_set<'v>(variable: &'v FuzzyVariable, term: &Identifier) -> Result<&'v FuzzySet> {
+    variable.set(term.name).ok_or_else(|| {
+        anyhow!(
+            "{} has no term {}, referenced at {}",
+            variable.name,
+            term.name,
+            location(term.span)
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzzy_logic::inference::FuzzySystem;
+    use crate::fuzzy_logic::{FuzzyController, Proposition};
+
+    const PROGRAM: &str = include_str!("../../../examples/fuzzy_program.fcl");
+
+    const ENGINE: &str = "Engine: steering
+InputVariable: obstacle
+  range: 0.000 1.000
+  lock-range: false
+  term: left Ramp 1.000 0.000
+  term: right Ramp 0.000 1.000
+OutputVariable: mSteer
+  range: 0.000 1.000
+  aggregation: Maximum
+  defuzzifier: Centroid 100
+  default: nan
+  term: left Ramp 1.000 0.000
+  term: right Ramp 0.000 1.000
+RuleBlock: mamdani
+  conjunction: none
+  implication: Minimum
+  rule: if obstacle is left then mSteer is right
+  rule: if obstacle is right then mSteer is left
+";
+
+    #[test]
+    fn test_compile_fcl() {
+        let mamdani = compile_fcl(PROGRAM).expect("Failed to compile FCL program");
+        assert_eq!(mamdani.rules().count(), 9);
+
+        let fuzzified = mamdani.fuzzify(&[-15.0, 30.0]);
+        assert_eq!(fuzzified[0], vec![1.0, 0.0, 0.0]);
+        assert_eq!(fuzzified[1], vec![0.5, 1.0 / 3.0, 0.0]);
+        assert_eq!(mamdani.inputs()[0].range(), Some((-20.0, 100.0)));
+
+        let ranged = PROGRAM.replacen("END_FUZZIFY", "  RANGE := (-30 .. 50);\nEND_FUZZIFY", 1);
+        let mamdani = compile_fcl(&ranged).unwrap();
+        assert_eq!(mamdani.inputs()[0].range(), Some((-30.0, 50.0)));
+        assert_eq!(mamdani.inputs()[1].range(), Some((0.0, 120.0)));
+    }
+
+    #[test]
+    fn test_compile_fcl_errors() {
+        let unknown_term = PROGRAM.replace(
+            "humidity IS low THEN heating IS high",
+            "humidity IS dry THEN heating IS high",
+        );
+        let error = compile_fcl(&unknown_term).err().unwrap().to_string();
+        assert!(error.contains("`humidity` has no term `dry`"), "{error}");
+
+        let unbounded = PROGRAM
+            .replace("TERM off := (0, 1) (20, 1) (40, 0);", "")
+            .replace("heating IS off", "heating IS low")
+            .replace(
+                "TERM low := (20, 0) (40, 1) (60, 1) (80, 0);",
+                "TERM low := 50;",
+            )
+            .replace(
+                "TERM medium := (40, 0) (60, 1) (80, 1) (100, 0);",
+                "TERM medium := 50;",
+            )
+            .replace(
+                "TERM high := (60, 0) (80, 1) (100, 1) (120, 0);",
+                "TERM high := 50;",
+            );
+        let error = compile_fcl(&unbounded).err().unwrap().to_string();
+        assert!(error.contains("heating needs a range"), "{error}");
+        let ranged = unbounded.replace("METHOD: COG;", "METHOD: COG;\n  RANGE := (0 .. 100);");
+        assert_eq!(
+            compile_fcl(&ranged).unwrap().outputs()[0].range(),
+            Some((0.0, 100.0))
+        );
+    }
+
+    #[test]
+    fn test_compile_fcl_conditions() {
+        let program = PROGRAM.replace(
+            "IF temperature IS cold AND humidity IS low THEN",
+            "IF temperature IS cold AND (humidity IS low OR humidity IS NOT high) AND \
+             NOT (temperature IS hot) THEN",
+        );
+        let mamdani = compile_fcl(&program).unwrap();
+        let rule = mamdani.rules().next().unwrap();
+        let RuleExpr::And(operands) = rule.condition() else {
+            panic!("Expected a conjunction");
+        };
+        assert_eq!(operands.len(), 3);
+        assert!(matches!(&operands[1], RuleExpr::Or(disjuncts) if disjuncts.len() == 2));
+        assert!(matches!(&operands[2], RuleExpr::Not(_)));
+        let tested: Vec<_> = rule
+            .condition()
+            .propositions()
+            .into_iter()
+            .map(|proposition| (proposition.variable.as_str(), proposition.term.as_str()))
+            .collect();
+        assert_eq!(
+            tested,
+            vec![
+                ("temperature", "cold"),
+                ("humidity", "low"),
+                ("humidity", "high"),
+                ("temperature", "hot")
+            ]
+        );
+
+        let unknown = program.replace("humidity IS NOT high", "humidity IS NOT dry");
+        let error = compile_fcl(&unknown).err().unwrap().to_string();
+        assert!(error.contains("`humidity` has no term `dry`"), "{error}");
+    }
+
+    #[test]
+    fn test_compile_outputs() {
+        let program = PROGRAM
+            .replace(
+                "  heating: REAL;      // Output variable for heating in percentage\n",
+                "  heating: REAL;\n  fan: REAL;\n",
+            )
+            .replace(
+                "END_DEFUZZIFY",
+                "END_DEFUZZIFY\n\nDEFUZZIFY fan\n  TERM off := (0, 1) (1, 0);\n  \
+                 TERM on := (0, 0) (1, 1);\n  METHOD: COG;\nEND_DEFUZZIFY",
+            )
+            .replace(
+                "humidity IS high THEN heating IS off;",
+                "humidity IS high THEN heating IS off, fan IS on;",
+            )
+            .replace(
+                "humidity IS low THEN heating IS off;",
+                "humidity IS low THEN fan IS off;",
+            );
+        let mamdani = compile_fcl(&program).unwrap();
+        let names: Vec<_> = mamdani
+            .outputs()
+            .iter()
+            .map(|output| output.name())
+            .collect();
+        assert_eq!(names, vec!["heating", "fan"]);
+        let consequents = mamdani.rules().nth(8).unwrap().consequents();
+        assert_eq!(
+            consequents,
+            [
+                Proposition::new("heating", "off"),
+                Proposition::new("fan", "on")
+            ]
+        );
+        let outputs = mamdani.infer_outputs(&[80.0, 100.0]);
+        // Only the fan being on concludes
+        assert!(outputs["fan"] > 0.5);
+        assert_eq!(outputs.get("heating"), Some(mamdani.infer(&[80.0, 100.0])));
+
+        let unknown = program.replace("fan IS on;", "temperature IS hot;");
+        let error = compile_fcl(&unknown).err().unwrap().to_string();
+        assert!(error.contains("not declared in VAR_OUTPUT"), "{error}");
+
+        let engine = format!(
+            "{ENGINE}OutputVariable: mSpeed\n  range: 0.000 1.000\n  term: slow Ramp 1.000 0.000\n"
+        )
+        .replace(
+            "then mSteer is left",
+            "then mSteer is left and mSpeed is slow",
+        );
+        let mamdani = compile_fll(&engine).unwrap();
+        assert_eq!(mamdani.outputs().len(), 2);
+        assert_eq!(mamdani.rules().nth(1).unwrap().consequents().len(), 2);
+        let different = engine.replace("term: slow", "aggregation: AlgebraicSum\n  term: slow");
+        let error = compile_fll(&different).err().unwrap().to_string();
+        assert!(error.contains("different aggregations"), "{error}");
+    }
+
+    #[test]
+    fn test_compile_weights() {
+        let program = PROGRAM.replace(
+            "humidity IS low THEN heating IS high;",
+            "humidity IS low THEN heating IS high WITH 0.5;",
+        );
+        let mamdani = compile_fcl(&program).unwrap();
+        let weights: Vec<_> = mamdani.rules().map(Rule::weight).collect();
+        assert_eq!(weights[..2], [0.5, 1.0]);
+        let unweighted = compile_fcl(PROGRAM).unwrap();
+        assert_ne!(
+            mamdani.infer(&[-15.0, 30.0]),
+            unweighted.infer(&[-15.0, 30.0])
+        );
+
+        let engine = ENGINE
+            .replace("then mSteer is left", "then mSteer is left with 0.25")
+            .replace(
+                "implication: Minimum",
+                "implication: Minimum\n  activation: Threshold >= 0.5",
+            );
+        let mamdani = compile_fll(&engine).unwrap();
+        assert_eq!(mamdani.rules().nth(1).unwrap().weight(), 0.25);
+        assert_eq!(
+            mamdani.rule_blocks()[0].activation,
+            Activation::Threshold(Comparison::GreaterOrEqual, 0.5)
+        );
+        // At 0.75, the first rule is activated at 0.25 and the second one at 0.25 × 0.75
+        assert_eq!(mamdani.aggregate(&[0.75])[0].conclusions.len(), 0);
+        assert_eq!(mamdani.aggregate(&[0.25])[0].conclusions.len(), 1);
+
+        let general = ENGINE.replace(
+            "implication: Minimum",
+            "implication: Minimum\n  activation: General",
+        );
+        assert_eq!(
+            compile_fll(&general).unwrap().rule_blocks()[0].activation,
+            Activation::General
+        );
+        let wrong = engine.replace(">= 0.5", "=> 0.5");
+        let error = compile_fll(&wrong).err().unwrap().to_string();
+        assert!(error.contains("activation Threshold => 0.5"), "{error}");
+        let wrong = engine.replace(">= 0.5", ">= high");
+        let error = compile_fll(&wrong).err().unwrap().to_string();
+        assert!(error.contains("Threshold >= high"), "{error}");
+        let wrong = engine.replace("with 0.25", "with 2");
+        let error = compile_fll(&wrong).err().unwrap().to_string();
+        assert!(error.contains("weight of 2"), "{error}");
+    }
+
+    #[test]
+    fn test_compile_defaults() {
+        let default = |mamdani: Result<Mamdani>| {
+            let mamdani = mamdani.unwrap();
+            let output = &mamdani.outputs()[0];
+            (output.default(), output.lock_previous())
+        };
+        let program = PROGRAM.replace("METHOD: COG;", "METHOD: COG;\n  DEFAULT := 10;");
+        assert_eq!(default(compile_fcl(&program)), (10.0, false));
+        let program = PROGRAM.replace("METHOD: COG;", "METHOD: COG;\n  DEFAULT := NC;");
+        let (value, lock_previous) = default(compile_fcl(&program));
+        assert!(value.is_nan() && lock_previous);
+
+        let engine = ENGINE.replace("default: nan", "default: 0.5\n  lock-previous: true");
+        assert_eq!(default(compile_fll(&engine)), (0.5, true));
+        assert!(default(compile_fll(ENGINE)).0.is_nan());
+    }
+
+    #[test]
+    fn test_compile_activations() {
+        let activation = |activation: &str| {
+            let engine = ENGINE.replace(
+                "implication: Minimum",
+                &format!("implication: Minimum\n  activation: {activation}"),
+            );
+            compile_fll(&engine).map(|mamdani| mamdani.rule_blocks()[0].activation)
+        };
+        assert_eq!(
+            activation("First").unwrap(),
+            Activation::First {
+                count: 1,
+                threshold: 0.0
+            }
+        );
+        assert_eq!(
+            activation("Last 2 0.5").unwrap(),
+            Activation::Last {
+                count: 2,
+                threshold: 0.5
+            }
+        );
+        assert_eq!(activation("Highest 2").unwrap(), Activation::Highest(2));
+        assert_eq!(activation("Lowest").unwrap(), Activation::Lowest(1));
+        assert_eq!(
+            activation("Threshold != 0.25").unwrap(),
+            Activation::Threshold(Comparison::NotEqual, 0.25)
+        );
+        assert_eq!(
+            activation("Proportional").unwrap(),
+            Activation::Proportional
+        );
+        for (wrong, message) in [
+            ("Highest 1.5", "activation Highest 1.5"),
+            ("General 1", "activation General 1"),
+            ("Threshold >= 2", "has a threshold of 2"),
+            ("Random", "activation Random at"),
+        ] {
+            let error = activation(wrong).err().unwrap().to_string();
+            assert!(error.contains(message), "{error}");
+        }
+    }
+
+    #[test]
+    fn test_compile_fll_hedges() {
+        let hedged = ENGINE.replace(
+            "if obstacle is left then",
+            "if obstacle is very left or obstacle is not somewhat right then",
+        );
+        let mamdani = compile_fll(&hedged).unwrap();
+        let RuleExpr::Or(operands) = mamdani.rules().next().unwrap().condition() else {
+            panic!("Expected a disjunction");
+        };
+        assert!(matches!(&operands[0], RuleExpr::Hedged(hedge, _) if hedge.name() == "very"));
+        let RuleExpr::Not(operand) = &operands[1] else {
+            panic!("Expected a negation");
+        };
+        assert!(matches!(&**operand, RuleExpr::Hedged(hedge, _) if hedge.name() == "somewhat"));
+        let strength = mamdani.aggregate(&[0.5])[0].conclusions[0].strength;
+        assert_eq!(strength, 0.25_f64.max(1.0 - 0.5_f64.sqrt()));
+    }
+
+    #[test]
+    fn test_compile_fcl_hedges() {
+        let program = PROGRAM.replace(
+            "IF temperature IS cold AND humidity IS low THEN",
+            "IF temperature IS NOT very extremely cold AND somewhat (humidity IS low) THEN",
+        );
+        let mamdani = compile_fcl(&program).unwrap();
+        let rule = mamdani.rules().next().unwrap();
+        let RuleExpr::And(operands) = rule.condition() else {
+            panic!("Expected a conjunction");
+        };
+        let RuleExpr::Not(operand) = &operands[0] else {
+            panic!("Expected a negation");
+        };
+        let RuleExpr::Hedged(very, operand) = &**operand else {
+            panic!("Expected a hedge");
+        };
+        assert_eq!(very.name(), "very");
+        assert!(matches!(&**operand, RuleExpr::Hedged(hedge, _) if hedge.name() == "extremely"));
+        assert!(matches!(&operands[1], RuleExpr::Hedged(hedge, _) if hedge.name() == "somewhat"));
+        assert_eq!(
+            rule.condition().to_string(),
+            "NOT (very (temperature IS extremely cold)) AND humidity IS somewhat low"
+        );
+
+        let unknown = program.replace("very extremely", "rather");
+        let error = compile_fcl(&unknown).err().unwrap().to_string();
+        assert!(error.contains("uses the hedge rather"), "{error}");
+    }
+
+    #[test]
+    fn test_compile_sugeno() {
+        let program = PROGRAM
+            .replace("TERM off := (0, 1) (20, 1) (40, 0);", "TERM off := 0;")
+            .replace(
+                "TERM low := (20, 0) (40, 1) (60, 1) (80, 0);",
+                "TERM low := 30;",
+            )
+            .replace(
+                "TERM medium := (40, 0) (60, 1) (80, 1) (100, 0);",
+                "TERM medium := 60;",
+            )
+            .replace(
+                "TERM high := (60, 0) (80, 1) (100, 1) (120, 0);",
+                "TERM high := 100;",
+            );
+        let sugeno = compile_fcl_sugeno(&program.replace("METHOD: COG;", "METHOD: COGS;")).unwrap();
+        assert_eq!(sugeno.outputs()[0].defuzzifier.name(), "WeightedAverage");
+        // Comfortable at 0.5 with normal and high humidity concludes low and medium equally
+        assert_eq!(sugeno.infer(&[10.0, 90.0]), 45.0);
+        let centroid = program.replace("METHOD: COG;", "METHOD: COG;\n  RANGE := (0 .. 100);");
+        let error = compile_fcl_sugeno(&centroid).err().unwrap().to_string();
+        assert!(error.contains("defuzzified by Centroid"), "{error}");
+        let error = compile_fcl_sugeno(PROGRAM).err().unwrap().to_string();
+        assert!(error.contains("term off at line 25"), "{error}");
+
+        let engine = "Engine: approximation
+InputVariable: x
+  range: 0.000 1.000
+  term: low Ramp 1.000 0.000
+  term: high Ramp 0.000 1.000
+OutputVariable: y
+  range: 0.000 10.000
+  defuzzifier: WeightedAverage TakagiSugeno
+  term: flat Constant 5.000
+  term: rising Linear 4.000 1.000
+RuleBlock: sugeno
+  rule: if x is low then y is flat
+  rule: if x is high then y is rising
+";
+        let sugeno = compile_fll_sugeno(engine).unwrap();
+        assert_eq!(sugeno.infer(&[0.25]), 0.75 * 5.0 + 0.25 * 2.0);
+        let wrong = engine.replace("Linear 4.000 1.000", "Linear 4.000 2.000 1.000");
+        let error = compile_fll_sugeno(&wrong).err().unwrap().to_string();
+        assert!(error.contains("2 coefficients for 1 input"), "{error}");
+        assert!(compile_fll_sugeno(ENGINE).is_err());
+    }
+
+    #[test]
+    fn test_compile_fcl_norms() {
+        let program = PROGRAM
+            .replace("AND: MIN;", "AND: PROD;\n  OR: ESUM;")
+            .replace("ACCU: MAX;", "ACT: PROD;\n  ACCU: BSUM;");
+        let mamdani = compile_fcl(&program).unwrap();
+        let block = &mamdani.rule_blocks()[0];
+        assert_eq!(block.name, "No1");
+        assert_eq!(block.conjunction.name(), "AlgebraicProduct");
+        assert_eq!(block.disjunction.name(), "EinsteinSum");
+        assert_eq!(block.implication.name(), "AlgebraicProduct");
+        assert_eq!(block.accumulation.name(), "BoundedSum");
+    }
+
+    #[test]
+    fn test_compile_fcl_methods() {
+        for (method, name) in [
+            ("COG", "Centroid"),
+            ("COA", "Bisector"),
+            ("MOM", "MeanOfMaximum"),
+            ("LM", "SmallestOfMaximum"),
+            ("RM", "LargestOfMaximum"),
+        ] {
+            let program = PROGRAM.replace("METHOD: COG;", &format!("METHOD: {method};"));
+            let mamdani = compile_fcl(&program).unwrap();
+            assert_eq!(mamdani.outputs()[0].defuzzifier.name(), name);
+        }
+
+        let program = PROGRAM.replace("METHOD: COG;", "METHOD: MOM;");
+        let (centroid, mean_of_maximum) = (
+            compile_fcl(PROGRAM).unwrap(),
+            compile_fcl(&program).unwrap(),
+        );
+        assert_ne!(
+            centroid.infer(&[-15.0, 30.0]),
+            mean_of_maximum.infer(&[-15.0, 30.0])
+        );
+    }
+
+    #[test]
+    fn test_compile_fcl_singletons() {
+        let program = |method| {
+            format!(
+                "FUNCTION_BLOCK Weighted
+                 VAR_INPUT level: REAL; END_VAR
+                 VAR_OUTPUT valve: REAL; END_VAR
+                 FUZZIFY level
+                   TERM low := (0, 1) (10, 0);
+                   TERM high := (0, 0) (10, 1);
+                 END_FUZZIFY
+                 DEFUZZIFY valve
+                   TERM closed := 2;
+                   TERM open := 8;
+                   METHOD: {method};
+                 END_DEFUZZIFY
+                 RULEBLOCK No1
+                   RULE 1 : IF level IS low THEN valve IS closed;
+                   RULE 2 : IF level IS high THEN valve IS open;
+                 END_RULEBLOCK
+                 END_FUNCTION_BLOCK"
+            )
+        };
+        for (method, name) in [("COGS", "WeightedAverage"), ("WSUM", "WeightedSum")] {
+            let mut controller = FuzzyController::from_fcl(&program(method)).unwrap();
+            assert_eq!(controller.infer.outputs()[0].defuzzifier.name(), name);
+            assert_eq!(controller.evaluate(&[5.0]), 5.0, "{method}");
+            assert_eq!(controller.evaluate(&[7.5]), 6.5, "{method}");
+
+            let fcl = controller.to_fcl("Weighted").unwrap();
+            assert!(
+                fcl.contains("  TERM closed := 2;\n  TERM open := 8;\n"),
+                "{fcl}"
+            );
+            assert_eq!(
+                FuzzyController::from_fcl(&fcl).unwrap().evaluate(&[7.5]),
+                6.5
+            );
+        }
+
+        let points = PROGRAM.replace("METHOD: COG;", "METHOD: COGS;");
+        let error = compile_fcl(&points).err().unwrap().to_string();
+        assert!(error.contains("is not a singleton"), "{error}");
+    }
+
+    #[test]
+    fn test_compile_fll() {
+        let mamdani = compile_fll(ENGINE).expect("Failed to compile FLL engine");
+        assert_eq!(mamdani.rules().count(), 2);
+        assert_eq!(mamdani.fuzzify(&[0.25])[0], vec![0.75, 0.25]);
+
+        assert_eq!(mamdani.outputs()[0].range(), Some((0.0, 1.0)));
+        assert_eq!(mamdani.outputs()[0].defuzzifier.resolution(), Some(100));
+        assert_eq!(mamdani.inputs()[0].range(), Some((0.0, 1.0)));
+        assert!(!mamdani.inputs()[0].lock_range());
+        let locked = ENGINE.replace("lock-range: false", "lock-range: true");
+        assert!(compile_fll(&locked).unwrap().inputs()[0].lock_range());
+
+        let norms = ENGINE
+            .replace("implication: Minimum", "implication: AlgebraicProduct")
+            .replace("conjunction: none", "conjunction: HamacherProduct")
+            .replace("aggregation: Maximum", "aggregation: NormalizedSum");
+        let mamdani = compile_fll(&norms).unwrap();
+        let block = &mamdani.rule_blocks()[0];
+        assert_eq!(block.name, "mamdani");
+        assert_eq!(block.conjunction.name(), "HamacherProduct");
+        assert_eq!(block.implication.name(), "AlgebraicProduct");
+        assert_eq!(block.accumulation.name(), "NormalizedSum");
+
+        let wrong = ENGINE.replace("conjunction: none", "conjunction: Maximum");
+        let error = compile_fll(&wrong).err().unwrap().to_string();
+        assert!(error.contains("conjunction: Maximum"), "{error}");
+        let wrong = ENGINE.replace("Centroid 100", "Median 100");
+        let error = compile_fll(&wrong).err().unwrap().to_string();
+        assert!(error.contains("defuzzifier Median"), "{error}");
+        let wrong = ENGINE.replace("Centroid 100", "WeightedAverage");
+        let error = compile_fll(&wrong).err().unwrap().to_string();
+        assert!(
+            error.contains("weighted outputs need Constant or Linear terms"),
+            "{error}"
+        );
+        let wrong = ENGINE.replace("implication: Minimum", "implication: Maximum");
+        let error = compile_fll(&wrong).err().unwrap().to_string();
+        assert!(error.contains("implication: Maximum"), "{error}");
+    }
+
+    #[test]
+    fn test_fll_fuzzy_set() {
+        let term = |input: &'static str| {
+            let engine = parse_fll(input).unwrap();
+            fll_fuzzy_set(&engine.inputs[0].terms[0])
+        };
+        let triangle = term("Engine:\nInputVariable: a\n  term: t Triangle 0 1 2 0.5\n").unwrap();
+        assert_eq!(triangle.membership(1.0), 0.5);
+        // The scaled shape keeps its parameters, it can be written or inverted
+        assert_eq!(format!("{:?}", triangle.function), "Triangular(0, 1, 2)");
+        assert_eq!(
+            triangle.points(),
+            Some(vec![(0.0, 0.0), (1.0, 0.5), (2.0, 0.0)])
+        );
+        let ramp = term("Engine:\nInputVariable: a\n  term: t Ramp 0 1 0.5\n").unwrap();
+        assert_eq!(ramp.inverse(0.25), Some(0.5));
+        let discrete = term("Engine:\nInputVariable: a\n  term: t Discrete 0 0 1 1\n").unwrap();
+        assert_eq!(discrete.membership(0.5), 0.5);
+        assert_eq!(
+            discrete.function.points(),
+            Some(vec![(0.0, 0.0), (1.0, 1.0)])
+        );
+        assert!(term("Engine:\nInputVariable: a\n  term: t Triangle 2 1 0\n").is_err());
+        assert!(term("Engine:\nInputVariable: a\n  term: t Ramp 0\n").is_err());
+        assert!(term("Engine:\nInputVariable: a\n  term: t Function x\n").is_err());
+        assert!(term("Engine:\nInputVariable: a\n  term: t Constant 1 2\n").is_err());
+        let linear = term("Engine:\nInputVariable: a\n  term: t Linear 1 2 3\n").unwrap();
+        assert_eq!(linear.function.evaluate_inputs(&[1.0, 1.0]), 6.0);
+        let binary = term("Engine:\nInputVariable: a\n  term: t Binary 0.5 inf\n").unwrap();
+        assert_eq!(binary.membership(0.75), 1.0);
+        let cosine = term("Engine:\nInputVariable: a\n  term: t Cosine 0.5 1.0\n").unwrap();
+        assert_eq!(format!("{:?}", cosine.function), "Cosine(0.5, 1)");
+    }
+}
@@ -0,0 +1,447 @@
+//! Diagnostics reported on FCL programs.
+//!
+//! Syntax errors come from the parser, semantic ones from [`check`] which walks a whole
+//! function block and reports every problem it finds instead of stopping at the first one.
+use std::collections::HashSet;
+use std::fmt;
+
+use pest::error::{Error as PestError, LineColLocation};
+use pest::{RuleType, Span};
+
+use super::ast::*;
+use super::parse_fcl;
+
+/// Problem found in an FCL program, located in its source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub message: String,
+    /// 1-based line of the start of the problem
+    pub line: usize,
+    /// 1-based column of the start of the problem
+    pub column: usize,
+    /// Source line of the problem with carets under the offending text
+    pub snippet: String,
+}
+
+impl Diagnostic {
+    /// Diagnostic pointing at `span`
+    pub fn new(message: impl Into<String>, span: Span) -> Self {
+        let start = span.start_pos();
+        let (line, column) = start.line_col();
+        let (end_line, end_column) = span.end_pos().line_col();
+        let source = start.line_of().trim_end_matches(['\r', '\n']);
+        let width = if end_line == line {
+            end_column - column
+        } else {
+            source.chars().count() + 1 - column
+        };
+
+        Self {
+            message: message.into(),
+            line,
+            column,
+            snippet: snippet(source, line, column, width),
+        }
+    }
+}
+
+/// Renders the source line with `width` carets starting at `column`
+fn snippet(source: &str, line: usize, column: usize, width: usize) -> String {
+    let gutter = " ".repeat(line.to_string().len());
+    // Keep the tabs of the source so the carets stay aligned
+    let indent: String = source
+        .chars()
+        .take(column - 1)
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    format!(
+        "{gutter} |\n{line} | {source}\n{gutter} | {indent}{}",
+        "^".repeat(width.max(1))
+    )
+}
+
+impl<R: RuleType> From<PestError<R>> for Diagnostic {
+    fn from(error: PestError<R>) -> Self {
+        let ((line, column), width) = match error.line_col {
+            LineColLocation::Pos(position) => (position, 1),
+            LineColLocation::Span(start, end) if start.0 == end.0 => (start, end.1 - start.1),
+            LineColLocation::Span(start, _) => (start, error.line().chars().count() + 1 - start.1),
+        };
+        let source = error.line().trim_end_matches(['\r', '\n']);
+
+        Self {
+            message: error.variant.message().into_owned(),
+            line,
+            column,
+            snippet: snippet(source, line, column, width),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "error: {}\n --> line {}, column {}\n{}",
+            self.message, self.line, self.column, self.snippet
+        )
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+/// Every diagnostic reported on a program
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Diagnostics(pub Vec<Diagnostic>);
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, diagnostic) in self.0.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "{diagnostic}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Diagnostics {}
+
+/// Parses and checks an FCL program, returning every problem found
+pub fn check_fcl(input: &str) -> Vec<Diagnostic> {
+    match parse_fcl(input) {
+        Ok(function_blocks) => function_blocks.iter().flat_map(check).collect(),
+        Err(diagnostic) => vec![diagnostic],
+    }
+}
+
+/// Semantic checks of a function block
+pub fn check(function_block: &FunctionBlock) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    let mut declared = HashSet::new();
+    for declaration in function_block.inputs.iter().chain(&function_block.outputs) {
+        if !declared.insert(declaration.name.name) {
+            diagnostics.push(Diagnostic::new(
+                format!("variable `{}` is declared twice", declaration.name.name),
+                declaration.name.span,
+            ));
+        }
+    }
+
+    for fuzzify in &function_block.fuzzify {
+        if !is_declared(&function_block.inputs, &fuzzify.variable) {
+            diagnostics.push(Diagnostic::new(
+                format!(
+                    "FUZZIFY refers to `{}` which is not declared in VAR_INPUT",
+                    fuzzify.variable.name
+                ),
+                fuzzify.variable.span,
+            ));
+        }
+        check_terms(&fuzzify.terms, &mut diagnostics);
+        check_range(fuzzify.range, &mut diagnostics);
+    }
+    for defuzzify in &function_block.defuzzify {
+        if !is_declared(&function_block.outputs, &defuzzify.variable) {
+            diagnostics.push(Diagnostic::new(
+                format!(
+                    "DEFUZZIFY refers to `{}` which is not declared in VAR_OUTPUT",
+                    defuzzify.variable.name
+                ),
+                defuzzify.variable.span,
+            ));
+        }
+        match defuzzify.method {
+            None => diagnostics.push(Diagnostic::new(
+                format!("DEFUZZIFY `{}` has no METHOD", defuzzify.variable.name),
+                defuzzify.variable.span,
+            )),
+            Some(method) if !method.function.is_method() => diagnostics.push(Diagnostic::new(
+                format!(
+                    "`{}` is not a defuzzification method, expected one of COG, COGS, COA, MOM, LM, RM, WSUM",
+                    method.function.keyword()
+                ),
+                method.span,
+            )),
+            Some(_) => {}
+        }
+        check_terms(&defuzzify.terms, &mut diagnostics);
+        check_range(defuzzify.range, &mut diagnostics);
+    }
+
+    for input in &function_block.inputs {
+        if find_fuzzify(function_block, input.name.name).is_none() {
+            diagnostics.push(Diagnostic::new(
+                format!("input `{}` has no FUZZIFY block", input.name.name),
+                input.name.span,
+            ));
+        }
+    }
+    for output in &function_block.outputs {
+        if find_defuzzify(function_block, output.name.name).is_none() {
+            diagnostics.push(Diagnostic::new(
+                format!("output `{}` has no DEFUZZIFY block", output.name.name),
+                output.name.span,
+            ));
+        }
+    }
+
+    for operator in function_block
+        .ruleblocks
+        .iter()
+        .flat_map(|block| &block.operators)
+    {
+        let functions = operator.kind.functions();
+        if !functions.contains(&operator.function) {
+            let expected: Vec<_> = functions
+                .iter()
+                .map(|function| function.keyword())
+                .collect();
+            diagnostics.push(Diagnostic::new(
+                format!(
+                    "`{}` is not a valid {} operator, expected one of {}",
+                    operator.function.keyword(),
+                    operator.kind.keyword(),
+                    expected.join(", ")
+                ),
+                operator.span,
+            ));
+        }
+    }
+
+    for rule in function_block
+        .ruleblocks
+        .iter()
+        .flat_map(|block| &block.rules)
+    {
+        if let Some(weight) = rule
+            .weight
+            .filter(|weight| !(0.0..=1.0).contains(&weight.value))
+        {
+            diagnostics.push(Diagnostic::new(
+                format!("RULE {} has a weight outside of [0, 1]", rule.number),
+                weight.span,
+            ));
+        }
+        for (variable, term) in rule.condition.tests() {
+            if !is_declared(&function_block.inputs, variable) {
+                diagnostics.push(Diagnostic::new(
+                    format!(
+                        "RULE {} tests `{}` which is not declared in VAR_INPUT",
+                        rule.number, variable.name
+                    ),
+                    variable.span,
+                ));
+            } else if let Some(fuzzify) = find_fuzzify(function_block, variable.name) {
+                check_term_exists(&fuzzify.terms, variable, term, &mut diagnostics);
+            }
+        }
+        for conclusion in &rule.conclusions {
+            if !is_declared(&function_block.outputs, &conclusion.variable) {
+                diagnostics.push(Diagnostic::new(
+                    format!(
+                        "RULE {} concludes on `{}` which is not declared in VAR_OUTPUT",
+                        rule.number, conclusion.variable.name
+                    ),
+                    conclusion.variable.span,
+                ));
+            } else if let Some(defuzzify) = find_defuzzify(function_block, conclusion.variable.name)
+            {
+                check_term_exists(
+                    &defuzzify.terms,
+                    &conclusion.variable,
+                    &conclusion.term,
+                    &mut diagnostics,
+                );
+            }
+        }
+    }
+
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.line, diagnostic.column));
+    diagnostics
+}
+
+fn is_declared(declarations: &[Declaration], variable: &Identifier) -> bool {
+    declarations
+        .iter()
+        .any(|declaration| declaration.name.name == variable.name)
+}
+
+fn find_fuzzify<'a, 'i>(
+    function_block: &'a FunctionBlock<'i>,
+    variable: &str,
+) -> Option<&'a Fuzzify<'i>> {
+    function_block
+        .fuzzify
+        .iter()
+        .find(|fuzzify| fuzzify.variable.name == variable)
+}
+
+fn find_defuzzify<'a, 'i>(
+    function_block: &'a FunctionBlock<'i>,
+    variable: &str,
+) -> Option<&'a Defuzzify<'i>> {
+    function_block
+        .defuzzify
+        .iter()
+        .find(|defuzzify| defuzzify.variable.name == variable)
+}
+
+/// Duplicate term names, point lists going backwards and memberships outside of `[0, 1]`
+fn check_terms(terms: &[Term], diagnostics: &mut Vec<Diagnostic>) {
+    let mut names = HashSet::new();
+    for term in terms {
+        if !names.insert(term.name.name) {
+            diagnostics.push(Diagnostic::new(
+                format!("term `{}` is defined twice", term.name.name),
+                term.name.span,
+            ));
+        }
+        let TermShape::Points(points) = &term.shape else {
+            continue;
+        };
+        for point in points
+            .iter()
+            .filter(|point| !(0.0..=1.0).contains(&point.y))
+        {
+            diagnostics.push(Diagnostic::new(
+                format!(
+                    "membership of term `{}` must be between 0 and 1, found {}",
+                    term.name.name, point.y
+                ),
+                point.span,
+            ));
+        }
+        for pair in points.windows(2) {
+            if pair[1].x < pair[0].x {
+                diagnostics.push(Diagnostic::new(
+                    format!(
+                        "points of term `{}` must have non-decreasing x, {} follows {}",
+                        term.name.name, pair[1].x, pair[0].x
+                    ),
+                    pair[1].span,
+                ));
+            }
+        }
+    }
+}
+
+fn check_range(range: Option<Range>, diagnostics: &mut Vec<Diagnostic>) {
+    if let Some(range) = range.filter(|range| range.min >= range.max) {
+        diagnostics.push(Diagnostic::new(
+            format!(
+                "RANGE must have min < max, got ({} .. {})",
+                range.min, range.max
+            ),
+            range.span,
+        ));
+    }
+}
+
+fn check_term_exists(
+    terms: &[Term],
+    variable: &Identifier,
+    term: &Identifier,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    if !terms
+        .iter()
+        .any(|candidate| candidate.name.name == term.name)
+    {
+        diagnostics.push(Diagnostic::new(
+            format!("`{}` has no term `{}`", variable.name, term.name),
+            term.span,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROGRAM: &str = include_str!("../../../examples/fuzzy_program.fcl");
+
+    #[test]
+    fn test_valid_program() {
+        assert_eq!(check_fcl(PROGRAM), vec![]);
+    }
+
+    #[test]
+    fn test_every_problem_is_reported() {
+        let program = PROGRAM
+            .replace("FUZZIFY humidity", "FUZZIFY humidty")
+            .replace("(80, 0) (100, 1) (120, 1)", "(80, 0) (70, 1) (120, 1)")
+            .replace("(0, 0) (20, 1) (40, 1) (60, 0)", "(0, 0) (20, 2) (40, 1) (60, 0)")
+            .replace(
+                "TERM hot :=",
+                "TERM cold := (-20, 1) (0, 0);\n  TERM hot :=",
+            )
+            .replace("  METHOD: COG;\n", "")
+            .replace(
+                "IF temperature IS cold AND humidity IS low",
+                "IF temperature IS chilly AND pressure IS low",
+            );
+        let diagnostics = check_fcl(&program);
+        let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+
+        assert_eq!(
+            messages,
+            vec![
+                "input `humidity` has no FUZZIFY block",
+                "membership of term `comfortable` must be between 0 and 1, found 2",
+                "term `cold` is defined twice",
+                "FUZZIFY refers to `humidty` which is not declared in VAR_INPUT",
+                "points of term `high` must have non-decreasing x, 70 follows 80",
+                "DEFUZZIFY `heating` has no METHOD",
+                "`temperature` has no term `chilly`",
+                "RULE 1 tests `pressure` which is not declared in VAR_INPUT",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_operators_and_methods() {
+        let program = PROGRAM
+            .replace("AND: MIN;", "AND: BSUM;")
+            .replace("METHOD: COG;", "METHOD: MAX;\n  RANGE := (100 .. 0);")
+            .replace("THEN heating IS high;", "THEN heating IS high WITH 1.5;");
+        let messages: Vec<_> = check_fcl(&program)
+            .into_iter()
+            .map(|diagnostic| diagnostic.message)
+            .collect();
+
+        assert_eq!(
+            messages,
+            vec![
+                "`MAX` is not a defuzzification method, expected one of COG, COGS, COA, MOM, LM, RM, WSUM",
+                "RANGE must have min < max, got (100 .. 0)",
+                "`BSUM` is not a valid AND operator, expected one of MIN, PROD, BDIF, DPROD, EPROD, HPROD, NMIN",
+                "RULE 1 has a weight outside of [0, 1]",
+                "RULE 2 has a weight outside of [0, 1]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_snippet() {
+        let program = PROGRAM.replace("heating IS high;", "heating IS hihg;");
+        let diagnostics = check_fcl(&program);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (36, 71));
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "error: `heating` has no term `hihg`\n --> line 36, column 71\n   |\n36 |   RULE 1 : IF temperature IS cold AND humidity IS low THEN heating IS hihg;\n   |                                                                       ^^^^"
+        );
+    }
+
+    #[test]
+    fn test_syntax_error() {
+        let program = PROGRAM.replace("TERM low := (0, 1)", "TERM low = (0, 1)");
+        let diagnostics = check_fcl(&program);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (19, 3));
+        assert!(diagnostics[0].snippet.ends_with("\n   |   ^"));
+    }
+}
@@ -0,0 +1,482 @@
+//! Parser of the FuzzyLite Language (FLL) used by the fuzzylite library.
+//!
+//! An FLL engine is parsed into a typed model keeping the [`Span`] of every node. Rules are
+//! represented with the same [`RuleStatement`] as FCL rules so both languages are compiled alike.
+use pest::iterators::{Pair, Pairs};
+use pest::{Parser, Span};
+use pest_derive::Parser;
+
+use super::ast::{
+    Conclusion, DefaultKind, DefaultValue, Identifier, Range, RuleExpr, RuleStatement, Weight,
+};
+use super::{fold_condition, identifier, next, Diagnostic};
+
+#[derive(Parser)]
+#[grammar = "fuzzy_logic/grammar/fll.pest"]
+pub struct FLLParser;
+
+type Result<T, E = Diagnostic> = std::result::Result<T, E>;
+
+/// `Engine: name`
+#[derive(Debug, Clone)]
+pub struct Engine<'i> {
+    pub name: Option<Identifier<'i>>,
+    pub description: Option<&'i str>,
+    pub inputs: Vec<Variable<'i>>,
+    pub outputs: Vec<Variable<'i>>,
+    pub ruleblocks: Vec<RuleBlock<'i>>,
+    pub span: Span<'i>,
+}
+
+/// `InputVariable: name` or `OutputVariable: name`.
+/// The aggregation, defuzzifier, default and lock-previous properties only exist on outputs
+#[derive(Debug, Clone)]
+pub struct Variable<'i> {
+    pub name: Identifier<'i>,
+    pub description: Option<&'i str>,
+    pub enabled: bool,
+    pub range: Option<Range<'i>>,
+    pub lock_range: bool,
+    pub terms: Vec<Term<'i>>,
+    pub aggregation: Option<Identifier<'i>>,
+    pub defuzzifier: Option<Defuzzifier<'i>>,
+    pub default: Option<DefaultValue<'i>>,
+    pub lock_previous: bool,
+    pub span: Span<'i>,
+}
+
+/// `term: name Kind parameters`
+#[derive(Debug, Clone, Copy)]
+pub struct Term<'i> {
+    pub name: Identifier<'i>,
+    /// Class of the term in fuzzylite, `Triangle`, `Ramp`, ...
+    pub kind: Identifier<'i>,
+    /// Parameters as written, they are not numbers for `Function` terms
+    pub parameters: &'i str,
+    pub span: Span<'i>,
+}
+
+impl<'i> Term<'i> {
+    /// Numeric parameters of the term, `None` if one of them is not a number
+    pub fn values(&self) -> Option<Vec<f64>> {
+        self.parameters
+            .split_whitespace()
+            .map(|value| scalar(value).ok())
+            .collect()
+    }
+}
+
+/// `defuzzifier: Name [resolution | type]`
+#[derive(Debug, Clone, Copy)]
+pub struct Defuzzifier<'i> {
+    pub name: Identifier<'i>,
+    /// Number of samples of integral defuzzifiers such as `Centroid 100`
+    pub resolution: Option<usize>,
+    /// Type of weighted defuzzifiers such as `WeightedAverage TakagiSugeno`
+    pub variant: Option<Identifier<'i>>,
+    pub span: Span<'i>,
+}
+
+/// `activation: Name [parameters]`
+#[derive(Debug, Clone, Copy)]
+pub struct Activation<'i> {
+    pub name: Identifier<'i>,
+    pub parameters: &'i str,
+    pub span: Span<'i>,
+}
+
+/// `RuleBlock: name`
+#[derive(Debug, Clone)]
+pub struct RuleBlock<'i> {
+    pub name: Option<Identifier<'i>>,
+    pub description: Option<&'i str>,
+    pub enabled: bool,
+    pub conjunction: Option<Identifier<'i>>,
+    pub disjunction: Option<Identifier<'i>>,
+    pub implication: Option<Identifier<'i>>,
+    pub activation: Option<Activation<'i>>,
+    pub rules: Vec<RuleStatement<'i>>,
+    pub span: Span<'i>,
+}
+
+/// Parses the input string and returns rules from the FLL grammar
+pub fn fll_parser(input: &str) -> Result<Pairs<'_, Rule>, pest::error::Error<Rule>> {
+    FLLParser::parse(Rule::fll, input)
+}
+
+/// Parses an FLL engine
+pub fn parse_fll(input: &str) -> Result<Engine<'_>> {
+    let mut pairs = fll_parser(input)?;
+    let fll = next(
+        &mut pairs,
+        Span::new(input, 0, 0).unwrap_or_else(|| unreachable!()),
+    )?;
+    let span = fll.as_span();
+    engine(next(&mut fll.into_inner(), span)?)
+}
+
+fn engine(pair: Pair<Rule>) -> Result<Engine> {
+    let mut engine = Engine {
+        name: None,
+        description: None,
+        inputs: Vec::new(),
+        outputs: Vec::new(),
+        ruleblocks: Vec::new(),
+        span: pair.as_span(),
+    };
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::name => engine.name = Some(identifier(pair)),
+            Rule::description => engine.description = Some(text(pair)),
+            Rule::input_variable => engine.inputs.push(variable(pair)?),
+            Rule::output_variable => engine.outputs.push(variable(pair)?),
+            _ => engine.ruleblocks.push(ruleblock(pair)?),
+        }
+    }
+
+    Ok(engine)
+}
+
+/// Trimmed text of a property whose value is free text
+fn text(pair: Pair<'_, Rule>) -> &str {
+    pair.into_inner()
+        .last()
+        .map(|text| text.as_str().trim())
+        .unwrap_or_default()
+}
+
+/// Value of a property whose only value is a name
+fn value(pair: Pair<Rule>) -> Result<Identifier> {
+    let span = pair.as_span();
+    Ok(identifier(next(&mut pair.into_inner(), span)?))
+}
+
+fn boolean(pair: Pair<Rule>) -> Result<bool> {
+    Ok(value(pair)?.name == "true")
+}
+
+fn scalar(value: &str) -> std::result::Result<f64, std::num::ParseFloatError> {
+    match value.trim_start_matches('+') {
+        "nan" | "-nan" => Ok(f64::NAN),
+        value => value.parse(),
+    }
+}
+
+fn number(pair: Pair<Rule>) -> Result<f64> {
+    scalar(pair.as_str()).map_err(|_| Diagnostic::new("invalid number", pair.as_span()))
+}
+
+fn variable(pair: Pair<Rule>) -> Result<Variable> {
+    let span = pair.as_span();
+    let mut inner = pair.into_inner();
+    let mut variable = Variable {
+        name: identifier(next(&mut inner, span)?),
+        description: None,
+        enabled: true,
+        range: None,
+        lock_range: false,
+        terms: Vec::new(),
+        aggregation: None,
+        defuzzifier: None,
+        default: None,
+        lock_previous: false,
+        span,
+    };
+    for pair in inner {
+        let span = pair.as_span();
+        match pair.as_rule() {
+            Rule::description => variable.description = Some(text(pair)),
+            Rule::enabled => variable.enabled = boolean(pair)?,
+            Rule::lock_range => variable.lock_range = boolean(pair)?,
+            Rule::lock_previous => variable.lock_previous = boolean(pair)?,
+            Rule::aggregation => variable.aggregation = Some(value(pair)?),
+            Rule::range => {
+                let mut numbers = pair.into_inner();
+                variable.range = Some(Range {
+                    min: number(next(&mut numbers, span)?)?,
+                    max: number(next(&mut numbers, span)?)?,
+                    span,
+                });
+            }
+            Rule::default => {
+                let value = number(next(&mut pair.into_inner(), span)?)?;
+                variable.default = Some(DefaultValue {
+                    kind: DefaultKind::Value(value),
+                    span,
+                });
+            }
+            Rule::defuzzifier => {
+                let mut inner = pair.into_inner();
+                let mut defuzzifier = Defuzzifier {
+                    name: identifier(next(&mut inner, span)?),
+                    resolution: None,
+                    variant: None,
+                    span,
+                };
+                if let Some(parameter) = inner.next() {
+                    match parameter.as_rule() {
+                        Rule::scalar => {
+                            let resolution = parameter.as_str().parse().map_err(|_| {
+                                Diagnostic::new(
+                                    "resolution must be a positive integer",
+                                    parameter.as_span(),
+                                )
+                            })?;
+                            defuzzifier.resolution = Some(resolution);
+                        }
+                        _ => defuzzifier.variant = Some(identifier(parameter)),
+                    }
+                }
+                variable.defuzzifier = Some(defuzzifier);
+            }
+            _ => {
+                let mut inner = pair.into_inner();
+                variable.terms.push(Term {
+                    name: identifier(next(&mut inner, span)?),
+                    kind: identifier(next(&mut inner, span)?),
+                    parameters: next(&mut inner, span)?.as_str().trim(),
+                    span,
+                });
+            }
+        }
+    }
+
+    Ok(variable)
+}
+
+fn ruleblock(pair: Pair<Rule>) -> Result<RuleBlock> {
+    let mut ruleblock = RuleBlock {
+        name: None,
+        description: None,
+        enabled: true,
+        conjunction: None,
+        disjunction: None,
+        implication: None,
+        activation: None,
+        rules: Vec::new(),
+        span: pair.as_span(),
+    };
+    for pair in pair.into_inner() {
+        let span = pair.as_span();
+        match pair.as_rule() {
+            Rule::name => ruleblock.name = Some(identifier(pair)),
+            Rule::description => ruleblock.description = Some(text(pair)),
+            Rule::enabled => ruleblock.enabled = boolean(pair)?,
+            Rule::conjunction => ruleblock.conjunction = Some(value(pair)?),
+            Rule::disjunction => ruleblock.disjunction = Some(value(pair)?),
+            Rule::implication => ruleblock.implication = Some(value(pair)?),
+            Rule::activation => {
+                let mut inner = pair.into_inner();
+                ruleblock.activation = Some(Activation {
+                    name: identifier(next(&mut inner, span)?),
+                    parameters: inner.next().map(|text| text.as_str().trim()).unwrap_or(""),
+                    span,
+                });
+            }
+            _ => {
+                let number = ruleblock.rules.len() as u32 + 1;
+                let statement = next(&mut pair.into_inner(), span)?;
+                ruleblock.rules.push(rule(statement, number)?);
+            }
+        }
+    }
+
+    Ok(ruleblock)
+}
+
+/// `if antecedent then consequents [with weight]`, numbered by its position in the block
+fn rule(pair: Pair<Rule>, position: u32) -> Result<RuleStatement> {
+    let span = pair.as_span();
+    let mut inner = pair.into_inner();
+    let condition = fold_condition(next(&mut inner, span)?, subcondition)?;
+    let mut conclusions = Vec::new();
+    let mut weight = None;
+    for pair in inner {
+        let span = pair.as_span();
+        match pair.as_rule() {
+            Rule::weight => {
+                weight = Some(Weight {
+                    value: number(next(&mut pair.into_inner(), span)?)?,
+                    span,
+                })
+            }
+            _ => {
+                let (variable, hedges, term) = proposition(pair)?;
+                if let Some(hedge) = hedges.first() {
+                    return Err(Diagnostic::new(
+                        "hedges are not supported in conclusions",
+                        hedge.as_span(),
+                    ));
+                }
+                conclusions.push(Conclusion {
+                    variable,
+                    term,
+                    span,
+                });
+            }
+        }
+    }
+
+    Ok(RuleStatement {
+        number: position,
+        condition,
+        conclusions,
+        weight,
+        span,
+    })
+}
+
+/// `variable is [hedges] term` or a parenthesized antecedent
+fn subcondition(pair: Pair<Rule>) -> Result<RuleExpr> {
+    if pair.as_rule() == Rule::antecedent {
+        return fold_condition(pair, subcondition);
+    }
+
+    let span = pair.as_span();
+    let (variable, hedges, term) = proposition(pair)?;
+    let mut expression = RuleExpr::Is {
+        variable,
+        term,
+        span,
+    };
+    for hedge in hedges.into_iter().rev() {
+        let operand = Box::new(expression);
+        expression = match hedge.as_str() {
+            "not" => RuleExpr::Not { operand, span },
+            _ => RuleExpr::Hedged {
+                hedge: identifier(hedge),
+                operand,
+                span,
+            },
+        };
+    }
+
+    Ok(expression)
+}
+
+/// Splits `variable is [hedges] term`
+fn proposition(pair: Pair<Rule>) -> Result<(Identifier, Vec<Pair<Rule>>, Identifier)> {
+    let span = pair.as_span();
+    let mut inner = pair.into_inner();
+    let variable = identifier(next(&mut inner, span)?);
+    let mut hedges: Vec<_> = inner.collect();
+    let term = hedges
+        .pop()
+        .ok_or_else(|| Diagnostic::new("incomplete proposition", span))?;
+    if term.as_rule() == Rule::any {
+        return Err(Diagnostic::new(
+            "hedge `any` is not supported",
+            term.as_span(),
+        ));
+    }
+
+    Ok((variable, hedges, identifier(term)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OBSTACLE_AVOIDANCE: &str = r#"#from examples/mamdani/ObstacleAvoidance.fll
+Engine: ObstacleAvoidance
+description: An engine for obstacle avoidance
+InputVariable: obstacle
+  description: direction of the obstacle
+  enabled: true
+  range: 0.000 1.000
+  lock-range: false
+  term: left Ramp 1.000 0.000
+  term: right Ramp 0.000 1.000
+OutputVariable: mSteer
+  description: steering direction of the vehicle
+  enabled: true
+  range: 0.000 1.000
+  lock-range: false
+  aggregation: Maximum
+  defuzzifier: Centroid 100
+  default: nan
+  lock-previous: false
+  term: left Ramp 1.000 0.000
+  term: right Ramp 0.000 1.000
+RuleBlock: mamdani
+  description: Mamdani inference for steering
+  enabled: true
+  conjunction: none
+  disjunction: none
+  implication: AlgebraicProduct
+  activation: General
+  rule: if obstacle is left then mSteer is right
+  rule: if obstacle is right then mSteer is left
+"#;
+
+    #[test]
+    fn test_parse_fll() {
+        let engine = parse_fll(OBSTACLE_AVOIDANCE).expect("Failed to parse FLL");
+        assert_eq!(engine.name.unwrap().name, "ObstacleAvoidance");
+        assert_eq!(engine.description, Some("An engine for obstacle avoidance"));
+
+        let obstacle = &engine.inputs[0];
+        assert_eq!(obstacle.name.name, "obstacle");
+        assert_eq!(obstacle.description, Some("direction of the obstacle"));
+        let range = obstacle.range.unwrap();
+        assert_eq!((range.min, range.max), (0.0, 1.0));
+        assert!(!obstacle.lock_range);
+        assert_eq!(obstacle.terms[0].name.name, "left");
+        assert_eq!(obstacle.terms[0].kind.name, "Ramp");
+        assert_eq!(obstacle.terms[0].values(), Some(vec![1.0, 0.0]));
+
+        let steer = &engine.outputs[0];
+        assert_eq!(steer.aggregation.unwrap().name, "Maximum");
+        let defuzzifier = steer.defuzzifier.unwrap();
+        assert_eq!(defuzzifier.name.name, "Centroid");
+        assert_eq!(defuzzifier.resolution, Some(100));
+        assert!(matches!(steer.default.unwrap().kind, DefaultKind::Value(value) if value.is_nan()));
+        assert!(!steer.lock_previous);
+
+        let ruleblock = &engine.ruleblocks[0];
+        assert_eq!(ruleblock.name.unwrap().name, "mamdani");
+        assert_eq!(ruleblock.conjunction.unwrap().name, "none");
+        assert_eq!(ruleblock.implication.unwrap().name, "AlgebraicProduct");
+        assert_eq!(ruleblock.activation.unwrap().name.name, "General");
+        assert_eq!(ruleblock.rules.len(), 2);
+        let rule = &ruleblock.rules[1];
+        assert_eq!(rule.number, 2);
+        assert!(matches!(rule.condition, RuleExpr::Is { term, .. } if term.name == "right"));
+        assert_eq!(rule.conclusions[0].term.name, "left");
+    }
+
+    #[test]
+    fn test_parse_fll_rules() {
+        let input = "Engine: rules\nRuleBlock: \n  rule: if a is not low and (b is high or c is low) then d is x and e is y with 0.5\n";
+        let engine = parse_fll(input).expect("Failed to parse FLL");
+        let rule = &engine.ruleblocks[0].rules[0];
+        assert_eq!(rule.weight.unwrap().value, 0.5);
+        assert_eq!(rule.conclusions.len(), 2);
+        let RuleExpr::And { left, right, .. } = &rule.condition else {
+            panic!("Expected a conjunction, got {:?}", rule.condition);
+        };
+        assert!(matches!(**left, RuleExpr::Not { .. }));
+        assert!(matches!(**right, RuleExpr::Or { .. }));
+
+        let input = "Engine: e\nRuleBlock: r\n  rule: if a is not very low then b is x\n";
+        let engine = parse_fll(input).expect("Failed to parse FLL");
+        let RuleExpr::Not { operand, .. } = &engine.ruleblocks[0].rules[0].condition else {
+            panic!("Expected a negation");
+        };
+        assert!(matches!(&**operand, RuleExpr::Hedged { hedge, .. } if hedge.name == "very"));
+    }
+
+    #[test]
+    fn test_parse_fll_errors() {
+        let any = "Engine: e\nRuleBlock: r\n  rule: if a is any then b is x\n";
+        let error = parse_fll(any).unwrap_err();
+        assert_eq!(error.message, "hedge `any` is not supported");
+        assert_eq!((error.line, error.column), (3, 17));
+        let hedge = "Engine: e\nRuleBlock: r\n  rule: if a is very low then b is very x\n";
+        let error = parse_fll(hedge).unwrap_err();
+        assert_eq!(error.message, "hedges are not supported in conclusions");
+
+        let syntax = "Engine: e\nInputVariable: a\n  range 0 1\n";
+        assert_eq!(parse_fll(syntax).unwrap_err().line, 3);
+    }
+}
@@ -0,0 +1,388 @@
+//! Writer emitting a fuzzy inference system as a canonical FCL program.
+//!
+//! The output is laid out like the FCL examples of the crate, so that compiling it back yields an
+//! equivalent system and writing that system again yields the same text.
+use std::fmt::Write;
+
+use anyhow::{bail, Result};
+use pest::Parser;
+
+use super::ast::FunctionOp;
+use super::{FCLParser, Rule as Grammar};
+use crate::fuzzy_logic::inference::{FuzzySystem, Mamdani};
+use crate::fuzzy_logic::{
+    hedges, Activation, FuzzyVariable, MembershipFunction, Proposition, RuleBlock, RuleExpr,
+};
+
+/// Writes `system` as a FUNCTION_BLOCK named `name`.
+/// Only piecewise linear sets, triangular, trapezoidal and singleton ones included, can be written.
+/// Rule blocks without a name are numbered, and activate every rule. An output cannot both have a
+/// default and lock its previous value, `DEFAULT := NC`. Resolutions and locks of the ranges are
+/// not written
+pub fn write_fcl(name: &str, system: &Mamdani) -> Result<String> {
+    let (inputs, outputs) = (system.inputs(), system.outputs());
+    let mut fcl = String::new();
+    writeln!(fcl, "FUNCTION_BLOCK {}\n", identifier(name)?)?;
+
+    writeln!(fcl, "VAR_INPUT")?;
+    for input in inputs {
+        writeln!(fcl, "  {}: REAL;", identifier(&input.name)?)?;
+    }
+    writeln!(fcl, "END_VAR\n")?;
+    writeln!(fcl, "VAR_OUTPUT")?;
+    for output in outputs {
+        writeln!(fcl, "  {}: REAL;", identifier(&output.name)?)?;
+    }
+    writeln!(fcl, "END_VAR\n")?;
+
+    for input in inputs {
+        writeln!(fcl, "FUZZIFY {}", input.name)?;
+        terms(&mut fcl, input)?;
+        if let Some((min, max)) = input.range {
+            writeln!(fcl, "  RANGE := ({min} .. {max});")?;
+        }
+        writeln!(fcl, "END_FUZZIFY\n")?;
+    }
+    for output in outputs {
+        writeln!(fcl, "DEFUZZIFY {}", output.name)?;
+        terms(&mut fcl, output)?;
+        let defuzzifier = output.defuzzifier.name();
+        match FunctionOp::from_defuzzifier(defuzzifier) {
+            Some(method) => writeln!(fcl, "  METHOD: {};", method.keyword())?,
+            None => bail!("defuzzifier {defuzzifier} has no FCL method"),
+        }
+        match (output.lock_previous, output.default) {
+            (false, default) if default.is_nan() => {}
+            (false, default) => writeln!(fcl, "  DEFAULT := {default};")?,
+            (true, default) if default.is_nan() => writeln!(fcl, "  DEFAULT := NC;")?,
+            (true, _) => bail!(
+                "output {} has a default and locks its previous value, FCL has either",
+                output.name
+            ),
+        }
+        if let Some((min, max)) = output.range {
+            writeln!(fcl, "  RANGE := ({min} .. {max});")?;
+        }
+        writeln!(fcl, "END_DEFUZZIFY\n")?;
+    }
+
+    for (index, block) in system.rule_blocks().iter().enumerate() {
+        let numbered = format!("No{}", index + 1);
+        let name = match block.name.as_str() {
+            "" => &numbered,
+            name => identifier(name)?,
+        };
+        writeln!(fcl, "RULEBLOCK {name}")?;
+        rule_block(&mut fcl, block)?;
+        writeln!(fcl, "END_RULEBLOCK\n")?;
+    }
+    writeln!(fcl, "END_FUNCTION_BLOCK")?;
+
+    Ok(fcl)
+}
+
+/// Rules were resolved against the variables when building the system, their names are valid
+fn rule_block(fcl: &mut String, block: &RuleBlock) -> Result<()> {
+    writeln!(fcl, "  AND: {};", operator(block.conjunction.name())?)?;
+    writeln!(fcl, "  OR: {};", operator(block.disjunction.name())?)?;
+    writeln!(fcl, "  ACT: {};", operator(block.implication.name())?)?;
+    writeln!(fcl, "  ACCU: {};\n", operator(block.accumulation.name())?)?;
+    if block.activation != Activation::General {
+        bail!(
+            "the activation {} of {} has no FCL equivalent",
+            block.activation.name(),
+            block.name
+        );
+    }
+    for (index, rule) in block.rules.iter().enumerate() {
+        let weight = match rule.weight {
+            1.0 => String::new(),
+            weight => format!(" WITH {weight}"),
+        };
+        let consequents: Vec<_> = rule
+            .consequents
+            .iter()
+            .map(|Proposition { variable, term }| format!("{variable} IS {term}"))
+            .collect();
+        writeln!(
+            fcl,
+            "  RULE {} : IF {} THEN {}{weight};",
+            index + 1,
+            condition(&rule.condition)?,
+            consequents.join(", ")
+        )?;
+    }
+    Ok(())
+}
+
+/// `expression` with its nested conjunctions and disjunctions parenthesized
+fn condition(expression: &RuleExpr) -> Result<String> {
+    check_condition(expression)?;
+    Ok(expression.to_string())
+}
+
+/// Fails on hedges the compiler does not know and empty conjunctions or disjunctions, which FCL
+/// cannot write
+fn check_condition(expression: &RuleExpr) -> Result<()> {
+    match expression {
+        RuleExpr::Is(_) => Ok(()),
+        RuleExpr::Not(operand) => check_condition(operand),
+        RuleExpr::Hedged(hedge, operand) => match hedges::hedge(hedge.name()) {
+            Some(_) => check_condition(operand),
+            None => bail!("hedge {} has no FCL equivalent", hedge.name()),
+        },
+        RuleExpr::And(operands) | RuleExpr::Or(operands) if operands.is_empty() => {
+            bail!("empty conditions cannot be written")
+        }
+        RuleExpr::And(operands) | RuleExpr::Or(operands) => {
+            operands.iter().try_for_each(check_condition)
+        }
+    }
+}
+
+/// FCL keyword of the norm named `norm`
+fn operator(norm: &str) -> Result<&'static str> {
+    match FunctionOp::from_norm(norm) {
+        Some(function) => Ok(function.keyword()),
+        None => bail!("norm {norm} has no FCL operator"),
+    }
+}
+
+/// Checks that `name` can be written as an FCL identifier
+fn identifier(name: &str) -> Result<&str> {
+    match FCLParser::parse(Grammar::identifier, name) {
+        Ok(pairs) if pairs.as_str() == name => Ok(name),
+        _ => bail!("`{name}` is not a valid FCL identifier"),
+    }
+}
+
+fn terms(fcl: &mut String, variable: &FuzzyVariable) -> Result<()> {
+    for set in &variable.fuzzy_sets {
+        // Singletons of weighted outputs are compiled to constants
+        if let MembershipFunction::Constant { value } = set.function {
+            writeln!(fcl, "  TERM {} := {value};", identifier(&set.name)?)?;
+            continue;
+        }
+        let Some(points) = set.points() else {
+            bail!(
+                "term {} of {} is not piecewise linear and cannot be written in FCL",
+                set.name,
+                variable.name
+            );
+        };
+        if points
+            .iter()
+            .any(|&(x, y)| !x.is_finite() || !y.is_finite())
+        {
+            bail!(
+                "term {} of {} has a point that is not finite",
+                set.name,
+                variable.name
+            );
+        }
+        let shape = match points[..] {
+            [(x0, 0.0), (x1, 1.0), (x2, 0.0)] if x0 == x1 && x1 == x2 => format!("{x1}"),
+            _ => points
+                .iter()
+                .map(|(x, y)| format!("({x}, {y})"))
+                .collect::<Vec<_>>()
+                .join(" "),
+        };
+        writeln!(fcl, "  TERM {} := {shape};", identifier(&set.name)?)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::fuzzy_logic::functions::triangular;
+    use crate::fuzzy_logic::{compile_fcl, hedges, Comparison, FuzzySet, Rule};
+
+    const PROGRAM: &str = include_str!("../../../examples/fuzzy_program.fcl");
+
+    #[test]
+    fn test_write_fcl() {
+        let mamdani = compile_fcl(PROGRAM).unwrap();
+        let fcl = write_fcl("RoomTempController", &mamdani).unwrap();
+        assert!(fcl.starts_with("FUNCTION_BLOCK RoomTempController\n\nVAR_INPUT\n"));
+        assert!(fcl.contains("  TERM cold := (-20, 1) (-10, 1) (0, 0);\n"));
+        assert!(fcl.contains(
+            "  RULE 9 : IF temperature IS hot AND humidity IS high THEN heating IS off;\n"
+        ));
+        assert!(fcl.ends_with("END_RULEBLOCK\n\nEND_FUNCTION_BLOCK\n"));
+    }
+
+    #[test]
+    fn test_write_fcl_is_canonical() {
+        let fcl = write_fcl("RoomTempController", &compile_fcl(PROGRAM).unwrap()).unwrap();
+        let written = write_fcl("RoomTempController", &compile_fcl(&fcl).unwrap()).unwrap();
+        assert_eq!(fcl, written);
+    }
+
+    #[test]
+    fn test_write_fcl_errors() {
+        let set = FuzzySet::from_points("low", vec![(0.0, 1.0), (1.0, 0.0)]);
+        let variable = |name: &str, set: FuzzySet| FuzzyVariable::new(name, vec![set]);
+        let system = |input: FuzzyVariable| {
+            let rule = Rule::new(&[(input.name(), "low")], ("out", "low"));
+            Mamdani::new(vec![input], vec![variable("out", set.clone())], vec![rule]).unwrap()
+        };
+        assert!(write_fcl("ok", &system(variable("level", set.clone()))).is_ok());
+        assert!(write_fcl("IS", &system(variable("level", set.clone()))).is_err());
+        assert!(write_fcl("ok", &system(variable("level 2", set.clone()))).is_err());
+
+        let triangle = MembershipFunction::triangular(0.0, 0.5, 1.0).unwrap();
+        let fcl = write_fcl(
+            "ok",
+            &system(variable("level", FuzzySet::with_function("low", triangle))),
+        )
+        .unwrap();
+        assert!(
+            fcl.contains("  TERM low := (0, 0) (0.5, 1) (1, 0);\n"),
+            "{fcl}"
+        );
+
+        let opaque = FuzzySet::new("low", Rc::from(triangular(0.0, 0.5, 1.0)));
+        let error = write_fcl("ok", &system(variable("level", opaque))).unwrap_err();
+        assert!(
+            error.to_string().contains("not piecewise linear"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn test_write_partial_rules() {
+        let program = PROGRAM.replace(
+            "IF temperature IS hot AND humidity IS high THEN",
+            "IF humidity IS high THEN",
+        );
+        let fcl = write_fcl("RoomTempController", &compile_fcl(&program).unwrap()).unwrap();
+        assert!(
+            fcl.contains("  RULE 9 : IF humidity IS high THEN heating IS off;\n"),
+            "{fcl}"
+        );
+    }
+
+    #[test]
+    fn test_write_conditions() {
+        let program = PROGRAM.replace(
+            "IF temperature IS hot AND humidity IS high THEN",
+            "IF (temperature IS hot AND humidity IS high) OR NOT (temperature IS hot OR \
+             humidity IS NOT low) THEN",
+        );
+        let fcl = write_fcl("RoomTempController", &compile_fcl(&program).unwrap()).unwrap();
+        assert!(
+            fcl.contains(
+                "  RULE 9 : IF (temperature IS hot AND humidity IS high) OR NOT (temperature IS \
+                 hot OR humidity IS NOT low) THEN heating IS off;\n"
+            ),
+            "{fcl}"
+        );
+
+        let set = FuzzySet::from_points("low", vec![(0.0, 1.0), (1.0, 0.0)]);
+        let variable = |name| FuzzyVariable::new(name, vec![set.clone()]);
+        let very = RuleExpr::Hedged(Rc::new(hedges::Very), Box::new(RuleExpr::is("in", "low")));
+        let somewhat = RuleExpr::Hedged(
+            Rc::new(hedges::Somewhat),
+            Box::new(RuleExpr::Not(Box::new(very.clone()))),
+        );
+        let rules = vec![
+            Rule::with_condition(very, ("out", "low")),
+            Rule::with_condition(somewhat, ("out", "low")),
+        ];
+        let hedged = Mamdani::new(vec![variable("in")], vec![variable("out")], rules).unwrap();
+        let fcl = write_fcl("hedged", &hedged).unwrap();
+        assert!(fcl.contains("  RULE 1 : IF in IS very low THEN out IS low;\n"), "{fcl}");
+        assert!(
+            fcl.contains("  RULE 2 : IF somewhat (NOT (in IS very low)) THEN out IS low;\n"),
+            "{fcl}"
+        );
+        let compiled = compile_fcl(&fcl).unwrap();
+        assert_eq!(write_fcl("hedged", &compiled).unwrap(), fcl);
+        assert_eq!(compiled.infer(&[0.5]), hedged.infer(&[0.5]));
+    }
+
+    #[test]
+    fn test_write_weights() {
+        let program = PROGRAM.replace(
+            "humidity IS high THEN heating IS off;",
+            "humidity IS high THEN heating IS off WITH 0.75;",
+        );
+        let mamdani = compile_fcl(&program).unwrap();
+        let fcl = write_fcl("RoomTempController", &mamdani).unwrap();
+        assert!(
+            fcl.contains(
+                "IF temperature IS hot AND humidity IS high THEN heating IS off WITH 0.75;\n"
+            ),
+            "{fcl}"
+        );
+
+        let set = FuzzySet::from_points("low", vec![(0.0, 1.0), (1.0, 0.0)]);
+        let variable = |name| FuzzyVariable::new(name, vec![set.clone()]);
+        let rule = Rule::new(&[("in", "low")], ("out", "low"));
+        let block = RuleBlock::new("threshold", vec![rule])
+            .with_activation(Activation::Threshold(Comparison::GreaterOrEqual, 0.5));
+        let mamdani =
+            Mamdani::with_rule_blocks(vec![variable("in")], vec![variable("out")], vec![block])
+                .unwrap();
+        let error = write_fcl("threshold", &mamdani).unwrap_err().to_string();
+        assert!(
+            error.contains("activation Threshold of threshold"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn test_write_defaults() {
+        for default in ["DEFAULT := 10;", "DEFAULT := NC;"] {
+            let program = PROGRAM.replace("METHOD: COG;", &format!("METHOD: COG;\n  {default}"));
+            let fcl = write_fcl("RoomTempController", &compile_fcl(&program).unwrap()).unwrap();
+            assert!(
+                fcl.contains(&format!("  METHOD: COG;\n  {default}\n")),
+                "{fcl}"
+            );
+        }
+
+        let set = FuzzySet::from_points("low", vec![(0.0, 1.0), (1.0, 0.0)]);
+        let input = FuzzyVariable::new("in", vec![set.clone()]);
+        let output = FuzzyVariable::new("out", vec![set])
+            .with_default(0.0)
+            .with_lock_previous(true);
+        let rules = vec![Rule::new(&[("in", "low")], ("out", "low"))];
+        let mamdani = Mamdani::new(vec![input], vec![output], rules).unwrap();
+        let error = write_fcl("locked", &mamdani).unwrap_err().to_string();
+        assert!(error.contains("output out has a default"), "{error}");
+    }
+
+    #[test]
+    fn test_write_norms() {
+        let program = PROGRAM
+            .replace("AND: MIN;", "AND: NMIN;")
+            .replace("ACCU: MAX;", "ACT: PROD;\n  ACCU: NSUM;")
+            .replace("METHOD: COG;", "METHOD: MOM;\n  RANGE := (0 .. 100);")
+            .replacen("END_FUZZIFY", "  RANGE := (-30 .. 50);\nEND_FUZZIFY", 1);
+        let fcl = write_fcl("RoomTempController", &compile_fcl(&program).unwrap()).unwrap();
+        assert!(
+            fcl.contains("RULEBLOCK No1\n  AND: NMIN;\n  OR: MAX;\n  ACT: PROD;\n  ACCU: NSUM;\n"),
+            "{fcl}"
+        );
+        assert!(
+            fcl.contains("  METHOD: MOM;\n  RANGE := (0 .. 100);\n"),
+            "{fcl}"
+        );
+        assert!(
+            fcl.contains("(70, 1) (100, 1);\n  RANGE := (-30 .. 50);\nEND_FUZZIFY"),
+            "{fcl}"
+        );
+    }
+
+    #[test]
+    fn test_write_singletons() {
+        let program = PROGRAM.replace("TERM off := (0, 1) (20, 1) (40, 0);", "TERM off := 10;");
+        let fcl = write_fcl("RoomTempController", &compile_fcl(&program).unwrap()).unwrap();
+        assert!(fcl.contains("  TERM off := 10;\n"), "{fcl}");
+    }
+}
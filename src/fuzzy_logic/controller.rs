@@ -1,3 +1,5 @@
+use crate::fuzzy_logic::control_language::{compile_fcl, compile_fll, write_fcl};
+use crate::fuzzy_logic::counterfactual::{Counterfactual, CounterfactualSearch};
 use crate::fuzzy_logic::inference::*;
 
 pub struct FuzzyController<T>
@@ -5,6 +7,8 @@ where
     T: FuzzySystem,
 {
     pub infer: T,
+    /// Outputs of the last evaluation, kept by the outputs locking their previous value
+    previous: Outputs,
 }
 
 impl<T> FuzzyController<T>
@@ -12,10 +16,107 @@ where
     T: FuzzySystem,
 {
     pub fn new(infer: T) -> Self {
-        Self { infer }
+        Self {
+            infer,
+            previous: Outputs::default(),
+        }
     }
 
-    pub fn evaluate(&self, input_values: &[f64]) -> f64 {
-        self.infer.infer(input_values)
+    /// Crisp value of the first output of the system, see [`FuzzyController::evaluate_outputs`]
+    pub fn evaluate(&mut self, input_values: &[f64]) -> f64 {
+        self.evaluate_outputs(input_values)
+            .iter()
+            .next()
+            .map_or(f64::NAN, |(_, value)| value)
+    }
+
+    /// Crisp value of every output of the system, by name. The outputs no rule fires on take
+    /// their default value, or their previous one when they lock it and it is not NaN.
+    /// The outputs are kept as the previous ones of the next evaluation
+    pub fn evaluate_outputs(&mut self, input_values: &[f64]) -> Outputs {
+        let mut outputs = self.infer.infer_outputs(input_values);
+        outputs.lock_previous(self.infer.outputs(), &self.previous);
+        self.previous = outputs.clone();
+        outputs
+    }
+
+    /// Outputs of the last evaluation, empty before the first one
+    pub fn previous(&self) -> &Outputs {
+        &self.previous
+    }
+
+    /// [`FuzzyController::evaluate_outputs`] of checked inputs, see [`FuzzySystem::check_inputs`]
+    pub fn try_evaluate_outputs(
+        &mut self,
+        input_values: &[f64],
+        policy: RangePolicy,
+    ) -> Result<Outputs, InferenceError> {
+        self.infer.check_inputs(input_values, policy)?;
+        Ok(self.evaluate_outputs(input_values))
+    }
+
+    /// Cheapest change of `input_values` moving an output across the threshold of `search`,
+    /// see [`CounterfactualSearch::search`]. The previous outputs are neither used nor updated
+    pub fn counterfactual(
+        &self,
+        input_values: &[f64],
+        search: &CounterfactualSearch,
+    ) -> anyhow::Result<Option<Counterfactual>> {
+        search.search(&self.infer, input_values)
+    }
+}
+
+impl FuzzyController<Mamdani> {
+    /// Builds a controller from the first function block of an FCL program
+    pub fn from_fcl(program: &str) -> anyhow::Result<Self> {
+        Ok(Self::new(compile_fcl(program)?))
+    }
+
+    /// Builds a controller from an FLL engine
+    pub fn from_fll(engine: &str) -> anyhow::Result<Self> {
+        Ok(Self::new(compile_fll(engine)?))
+    }
+
+    /// Writes the controller as an FCL function block named `name`
+    pub fn to_fcl(&self, name: &str) -> anyhow::Result<String> {
+        write_fcl(name, &self.infer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzzy_logic::{FuzzySet, FuzzyVariable, Rule};
+
+    #[test]
+    fn test_lock_previous() {
+        let low = FuzzySet::from_points("low", vec![(0.0, 1.0), (0.5, 0.0)]);
+        let output = |lock_previous| {
+            FuzzyVariable::new("out", vec![low.clone()])
+                .with_range(0.0, 1.0)
+                .with_default(1.0)
+                .with_lock_previous(lock_previous)
+        };
+        let controller = |lock_previous| {
+            let rules = vec![Rule::new(&[("in", "low")], ("out", "low"))];
+            let input = FuzzyVariable::new("in", vec![low.clone()]);
+            let mamdani = Mamdani::new(vec![input], vec![output(lock_previous)], rules);
+            FuzzyController::new(mamdani.unwrap())
+        };
+
+        let mut locked = controller(true);
+        // Nothing fires yet, the default is taken
+        assert_eq!(locked.evaluate(&[1.0]), 1.0);
+        let fired = locked.evaluate(&[0.0]);
+        assert!(fired < 0.5);
+        assert_eq!(locked.evaluate(&[1.0]), fired);
+        // The previous value is kept rather than defaulted
+        assert!(!locked.evaluate_outputs(&[1.0]).is_default("out"));
+        assert_eq!(locked.previous()["out"], fired);
+
+        let mut unlocked = controller(false);
+        unlocked.evaluate(&[0.0]);
+        assert_eq!(unlocked.evaluate(&[1.0]), 1.0);
+        assert!(unlocked.previous().is_default("out"));
     }
 }
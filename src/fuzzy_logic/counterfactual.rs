@@ -0,0 +1,457 @@
+//! Counterfactual explanations, the smallest change of the inputs of a system moving one of its
+//! outputs across a threshold, see [`FuzzyController::counterfactual`].
+//!
+//! The cost of a change is the sum over the inputs of their cost times the distance they move,
+//! as a fraction of their range. The search walks a lattice of `steps` values per range, cheapest
+//! changes first, and then moves the inputs back towards their values as long as the output
+//! still crosses the threshold. The inputs that can change start from their values clamped into
+//! their range, the move into the range counting in the cost.
+//!
+//! [`FuzzyController::counterfactual`]: super::FuzzyController::counterfactual
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashSet};
+use std::fmt;
+
+use anyhow::{bail, Result};
+
+use crate::fuzzy_logic::inference::{FuzzySystem, InferenceError};
+
+/// Threshold of an output to cross and the inputs that can change to cross it
+#[derive(Debug, Clone)]
+pub struct CounterfactualSearch {
+    threshold: f64,
+    output: Option<String>,
+    costs: Vec<(String, f64)>,
+    immutable: Vec<String>,
+    steps: usize,
+    max_evaluations: usize,
+}
+
+/// Change of one input
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub variable: String,
+    pub from: f64,
+    pub to: f64,
+}
+
+/// Inputs moving an output across a threshold
+#[derive(Debug, Clone, PartialEq)]
+pub struct Counterfactual {
+    pub output: String,
+    pub threshold: f64,
+    /// Inputs that changed, in the order of the inputs of the system
+    pub changes: Vec<Change>,
+    pub inputs: Vec<f64>,
+    /// Value of the output for the changed inputs
+    pub value: f64,
+    pub cost: f64,
+}
+
+impl CounterfactualSearch {
+    /// Search of the inputs moving the first output across `threshold`, from below to at least
+    /// `threshold` or from there to below it
+    pub fn new(threshold: f64) -> Self {
+        Self {
+            threshold,
+            output: None,
+            costs: Vec::new(),
+            immutable: Vec::new(),
+            steps: 20,
+            max_evaluations: 10_000,
+        }
+    }
+
+    /// Sets the output to move across the threshold
+    pub fn with_output(mut self, output: &str) -> Self {
+        self.output = Some(output.to_string());
+        self
+    }
+
+    /// Sets the cost of moving `input` across its whole range, 1 by default
+    pub fn with_cost(mut self, input: &str, cost: f64) -> Self {
+        self.costs.push((input.to_string(), cost));
+        self
+    }
+
+    /// Keeps `input` at its value
+    pub fn with_immutable(mut self, input: &str) -> Self {
+        self.immutable.push(input.to_string());
+        self
+    }
+
+    /// Sets the number of steps the ranges of the inputs are walked in
+    pub fn with_steps(mut self, steps: usize) -> Self {
+        self.steps = steps;
+        self
+    }
+
+    /// Sets the number of inferences after which the search gives up
+    pub fn with_max_evaluations(mut self, max_evaluations: usize) -> Self {
+        self.max_evaluations = max_evaluations;
+        self
+    }
+
+    /// Cheapest change of `input_values` moving the output of `system` across the threshold,
+    /// `None` when no change within the ranges of the inputs does. The changes start from
+    /// `input_values`, even those outside of their range.
+    /// Fails when the search names unknown variables, a cost is negative or an input that can
+    /// change has no range
+    pub fn search(
+        &self,
+        system: &impl FuzzySystem,
+        input_values: &[f64],
+    ) -> Result<Option<Counterfactual>> {
+        let inputs = system.inputs();
+        if input_values.len() != inputs.len() {
+            bail!(InferenceError::Arity {
+                expected: inputs.len(),
+                found: input_values.len(),
+            });
+        }
+        let output = match &self.output {
+            Some(name) => match system.outputs().iter().position(|o| o.name == *name) {
+                Some(output) => output,
+                None => bail!("{name} is not an output variable"),
+            },
+            None if system.outputs().is_empty() => bail!("the system has no output variable"),
+            None => 0,
+        };
+        for name in self
+            .costs
+            .iter()
+            .map(|(name, _)| name)
+            .chain(&self.immutable)
+        {
+            if !inputs.iter().any(|input| input.name == *name) {
+                bail!("{name} is not an input variable");
+            }
+        }
+
+        // Range and cost of each input that can change, which starts from its value in the range
+        let mut mutable = Vec::new();
+        let mut origin = input_values.to_vec();
+        for (index, input) in inputs.iter().enumerate() {
+            let cost = self
+                .costs
+                .iter()
+                .rev()
+                .find(|(name, _)| *name == input.name)
+                .map_or(1.0, |&(_, cost)| cost);
+            if cost.is_nan() || cost < 0.0 {
+                bail!(
+                    "input {} has a cost of {cost}, it must not be negative",
+                    input.name
+                );
+            }
+            if self.immutable.contains(&input.name) || cost == f64::INFINITY {
+                continue;
+            }
+            let Some((min, max)) = input.range() else {
+                bail!("input {} has no range to search", input.name);
+            };
+            origin[index] = origin[index].clamp(min, max);
+            mutable.push(Mutable {
+                index,
+                min,
+                max,
+                cost,
+            });
+        }
+
+        let infer = |values: &[f64]| system.infer_outputs(values).iter().nth(output).unwrap().1;
+        let initial = infer(input_values);
+        let above = initial >= self.threshold;
+        let crosses = |value: f64| !value.is_nan() && (value >= self.threshold) != above;
+        let steps = self.steps.max(1) as f64;
+        let values = |offsets: &[i64]| {
+            let mut values = origin.clone();
+            for (offset, input) in offsets.iter().zip(&mutable) {
+                let step = (input.max - input.min) / steps;
+                values[input.index] =
+                    (origin[input.index] + *offset as f64 * step).clamp(input.min, input.max);
+            }
+            values
+        };
+        let cost = |values: &[f64]| -> f64 {
+            mutable
+                .iter()
+                .filter(|input| input.max > input.min)
+                .map(|input| {
+                    let distance = (values[input.index] - input_values[input.index]).abs();
+                    input.cost * distance / (input.max - input.min)
+                })
+                .sum()
+        };
+
+        // Cheapest lattice point crossing the threshold, walking one step of an input at a time
+        let start = vec![0; mutable.len()];
+        let mut visited = HashSet::from([start.clone()]);
+        let mut queue = BinaryHeap::from([Node {
+            cost: cost(&origin),
+            offsets: start,
+        }]);
+        let mut evaluations = 1;
+        let mut found = None;
+        while let Some(Node { offsets, .. }) = queue.pop() {
+            let point = values(&offsets);
+            if point != input_values {
+                if evaluations >= self.max_evaluations {
+                    break;
+                }
+                evaluations += 1;
+                if crosses(infer(&point)) {
+                    found = Some(point);
+                    break;
+                }
+            }
+            for (position, input) in mutable.iter().enumerate() {
+                for direction in [-1, 1] {
+                    let mut next = offsets.clone();
+                    next[position] += direction;
+                    let value = values(&next)[input.index];
+                    // Stop at the bounds of the range
+                    if value == point[input.index] || !visited.insert(next.clone()) {
+                        continue;
+                    }
+                    queue.push(Node {
+                        cost: cost(&values(&next)),
+                        offsets: next,
+                    });
+                }
+            }
+        }
+        let Some(mut point) = found else {
+            return Ok(None);
+        };
+
+        // Move the inputs back towards their values while the output still crosses
+        let between = |t: f64| -> Vec<f64> {
+            origin
+                .iter()
+                .zip(&point)
+                .map(|(&from, &to)| from + t * (to - from))
+                .collect()
+        };
+        let (mut low, mut high) = (0.0, 1.0);
+        for _ in 0..32 {
+            let middle = (low + high) / 2.0;
+            if crosses(infer(&between(middle))) {
+                high = middle;
+            } else {
+                low = middle;
+            }
+        }
+        point = between(high);
+
+        let changes = inputs
+            .iter()
+            .zip(input_values.iter().zip(&point))
+            .filter(|(_, (from, to))| from != to)
+            .map(|(input, (&from, &to))| Change {
+                variable: input.name.clone(),
+                from,
+                to,
+            })
+            .collect();
+        Ok(Some(Counterfactual {
+            output: system.outputs()[output].name.clone(),
+            threshold: self.threshold,
+            changes,
+            value: infer(&point),
+            cost: cost(&point),
+            inputs: point,
+        }))
+    }
+}
+
+/// `output would reach threshold if x rose from a to b and y fell from c to d`, the values
+/// written with the precision of the formatter, 1 decimal by default
+impl fmt::Display for Counterfactual {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let precision = f.precision().unwrap_or(1);
+        let verb = if self.value >= self.threshold {
+            "reach"
+        } else {
+            "fall below"
+        };
+        write!(f, "{} would {verb} {} if ", self.output, self.threshold)?;
+        for (index, change) in self.changes.iter().enumerate() {
+            if index > 0 {
+                let last = index + 1 == self.changes.len();
+                f.write_str(if last { " and " } else { ", " })?;
+            }
+            let verb = if change.to > change.from {
+                "rose"
+            } else {
+                "fell"
+            };
+            write!(
+                f,
+                "{} {verb} from {:.*} to {:.*}",
+                change.variable, precision, change.from, precision, change.to
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Input that can change, by its index among the inputs of the system
+struct Mutable {
+    index: usize,
+    min: f64,
+    max: f64,
+    cost: f64,
+}
+
+/// Lattice point, the number of steps each input that can change moved, by its cost
+struct Node {
+    cost: f64,
+    offsets: Vec<i64>,
+}
+
+impl PartialEq for Node {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Node {}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Reversed, the cheapest node is the greatest to pop first from a max-heap
+impl Ord for Node {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzzy_logic::inference::Mamdani;
+    use crate::fuzzy_logic::{FuzzyController, FuzzySet, FuzzyVariable, Rule};
+
+    fn variable(name: &str) -> FuzzyVariable {
+        let low = FuzzySet::from_points("low", vec![(0.0, 1.0), (10.0, 0.0)]);
+        let high = FuzzySet::from_points("high", vec![(0.0, 0.0), (10.0, 1.0)]);
+        FuzzyVariable::new(name, vec![low, high]).with_range(0.0, 10.0)
+    }
+
+    fn controller() -> FuzzyController<Mamdani> {
+        let rules = vec![
+            Rule::new(&[("reputation", "low")], ("trust", "low")),
+            Rule::new(&[("history", "low")], ("trust", "low")),
+            Rule::new(
+                &[("reputation", "high"), ("history", "high")],
+                ("trust", "high"),
+            ),
+        ];
+        let inputs = vec![variable("reputation"), variable("history")];
+        let mamdani = Mamdani::new(inputs, vec![variable("trust")], rules).unwrap();
+        FuzzyController::new(mamdani)
+    }
+
+    #[test]
+    fn test_counterfactual() {
+        let mut controller = controller();
+        let inputs = [8.0, 4.0];
+        let initial = controller.evaluate(&inputs);
+        assert!(initial < 5.0);
+
+        let search = CounterfactualSearch::new(5.0).with_immutable("reputation");
+        let counterfactual = controller
+            .counterfactual(&inputs, &search)
+            .unwrap()
+            .unwrap();
+        assert_eq!(counterfactual.changes.len(), 1);
+        let change = &counterfactual.changes[0];
+        assert_eq!((change.variable.as_str(), change.from), ("history", 4.0));
+        assert!(change.to > 4.0);
+        assert!(counterfactual.value >= 5.0);
+        // Slightly less of a change does not reach the threshold
+        let closer = controller.evaluate(&[8.0, change.to - 1e-3]);
+        assert!(closer < 5.0);
+        assert_eq!(
+            counterfactual.to_string(),
+            format!(
+                "trust would reach 5 if history rose from 4.0 to {:.1}",
+                change.to
+            )
+        );
+
+        // Lowering the trust below where it is, cheaper by the history unless it costs more
+        let below = CounterfactualSearch::new(initial - 1.0);
+        let counterfactual = controller.counterfactual(&inputs, &below).unwrap().unwrap();
+        assert!(counterfactual.value < initial - 1.0);
+        assert!(counterfactual
+            .to_string()
+            .starts_with("trust would fall below"));
+        assert_eq!(counterfactual.changes[0].variable, "history");
+        let expensive = below.with_cost("history", 100.0);
+        let counterfactual = controller
+            .counterfactual(&inputs, &expensive)
+            .unwrap()
+            .unwrap();
+        assert_eq!(counterfactual.changes.len(), 1);
+        assert_eq!(counterfactual.changes[0].variable, "reputation");
+        assert!(counterfactual.changes[0].to < 8.0);
+    }
+
+    #[test]
+    fn test_out_of_range() {
+        let mut controller = controller();
+        // The reputation is above its range, moving it back into the range costs
+        let inputs = [12.0, 4.0];
+        let initial = controller.evaluate(&inputs);
+        let search = CounterfactualSearch::new(initial - 1.0).with_immutable("history");
+        let counterfactual = controller
+            .counterfactual(&inputs, &search)
+            .unwrap()
+            .unwrap();
+        let change = &counterfactual.changes[0];
+        assert_eq!(
+            (change.variable.as_str(), change.from),
+            ("reputation", 12.0)
+        );
+        assert!(change.to < 10.0);
+        assert_eq!(counterfactual.cost, (12.0 - change.to) / 10.0);
+        assert_eq!(counterfactual.inputs[1], 4.0);
+    }
+
+    #[test]
+    fn test_unreachable() {
+        let controller = controller();
+        let search = CounterfactualSearch::new(5.0)
+            .with_immutable("reputation")
+            .with_immutable("history");
+        assert_eq!(
+            controller.counterfactual(&[8.0, 4.0], &search).unwrap(),
+            None
+        );
+        let search = CounterfactualSearch::new(20.0);
+        assert_eq!(
+            controller.counterfactual(&[8.0, 4.0], &search).unwrap(),
+            None
+        );
+        let error = |search: CounterfactualSearch| {
+            controller
+                .counterfactual(&[8.0, 4.0], &search)
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(
+            error(CounterfactualSearch::new(5.0).with_immutable("age")),
+            "age is not an input variable"
+        );
+        assert_eq!(
+            error(CounterfactualSearch::new(5.0).with_cost("history", -1.0)),
+            "input history has a cost of -1, it must not be negative"
+        );
+    }
+}
@@ -0,0 +1,371 @@
+//! Defuzzifiers turning the aggregated output fuzzy set into a crisp value.
+//!
+//! Integral defuzzifiers sample the aggregated set over the range of the output at a resolution,
+//! weighted ones combine the values of the conclusions by their firing strength. The
+//! implementations and their names follow fuzzylite.
+use std::rc::Rc;
+
+use super::inference::AggregatedSet;
+
+/// Number of samples of integral defuzzifiers when none is given
+pub const DEFAULT_RESOLUTION: usize = 100;
+
+/// Crisp value of an aggregated output fuzzy set
+pub trait Defuzzifier {
+    fn name(&self) -> &'static str;
+
+    /// Number of samples of the output range, `None` for defuzzifiers that do not sample it
+    fn resolution(&self) -> Option<usize>;
+
+    /// NaN when no rule fires or, for integral defuzzifiers, when the output has no range
+    fn defuzzify(&self, aggregated: &AggregatedSet) -> f64;
+}
+
+/// Center of gravity of the area under the aggregated set
+#[derive(Debug, Clone, Copy)]
+pub struct Centroid {
+    pub resolution: usize,
+}
+
+/// Value dividing the area under the aggregated set in two equal halves
+#[derive(Debug, Clone, Copy)]
+pub struct Bisector {
+    pub resolution: usize,
+}
+
+/// Middle of the first plateau of highest membership
+#[derive(Debug, Clone, Copy)]
+pub struct MeanOfMaximum {
+    pub resolution: usize,
+}
+
+/// Smallest value of highest membership
+#[derive(Debug, Clone, Copy)]
+pub struct SmallestOfMaximum {
+    pub resolution: usize,
+}
+
+/// Largest value of highest membership
+#[derive(Debug, Clone, Copy)]
+pub struct LargestOfMaximum {
+    pub resolution: usize,
+}
+
+/// Average of the values of the conclusions weighted by their firing strength, for Sugeno
+/// systems whose conclusions are constant or linear
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WeightedAverage;
+
+/// Sum of the values of the conclusions weighted by their firing strength, for Sugeno systems
+/// whose conclusions are constant or linear
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WeightedSum;
+
+macro_rules! integral {
+    ($($defuzzifier:ident),*) => {
+        $(
+            impl Default for $defuzzifier {
+                fn default() -> Self {
+                    Self {
+                        resolution: DEFAULT_RESOLUTION,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+integral!(
+    Centroid,
+    Bisector,
+    MeanOfMaximum,
+    SmallestOfMaximum,
+    LargestOfMaximum
+);
+
+/// Midpoints of `resolution` intervals evenly dividing the range with their membership
+fn samples(aggregated: &AggregatedSet, resolution: usize) -> Option<Vec<(f64, f64)>> {
+    let (min, max) = aggregated.range.filter(|_| resolution > 0)?;
+    let dx = (max - min) / resolution as f64;
+    let samples = (0..resolution)
+        .map(|i| min + (i as f64 + 0.5) * dx)
+        .map(|x| (x, aggregated.membership(x)))
+        .collect();
+    Some(samples)
+}
+
+/// Highest membership of the samples with the first and the last of the first plateau reaching
+/// it and the last sample reaching it
+fn maximum(aggregated: &AggregatedSet, resolution: usize) -> Option<(f64, f64, f64)> {
+    let samples = samples(aggregated, resolution)?;
+    let (mut height, mut plateau) = (0.0, true);
+    let (mut smallest, mut plateau_end, mut largest) = (f64::NAN, f64::NAN, f64::NAN);
+    for (x, y) in samples {
+        if y > height {
+            (height, plateau) = (y, true);
+            (smallest, plateau_end, largest) = (x, x, x);
+        } else if y == height && height > 0.0 {
+            largest = x;
+            if plateau {
+                plateau_end = x;
+            }
+        } else {
+            plateau = false;
+        }
+    }
+    Some((smallest, plateau_end, largest))
+}
+
+impl Defuzzifier for Centroid {
+    fn name(&self) -> &'static str {
+        "Centroid"
+    }
+
+    fn resolution(&self) -> Option<usize> {
+        Some(self.resolution)
+    }
+
+    fn defuzzify(&self, aggregated: &AggregatedSet) -> f64 {
+        let Some(samples) = samples(aggregated, self.resolution) else {
+            return f64::NAN;
+        };
+        let (area, moment) = samples.iter().fold((0.0, 0.0), |(area, moment), &(x, y)| {
+            (area + y, moment + x * y)
+        });
+        moment / area
+    }
+}
+
+impl Defuzzifier for Bisector {
+    fn name(&self) -> &'static str {
+        "Bisector"
+    }
+
+    fn resolution(&self) -> Option<usize> {
+        Some(self.resolution)
+    }
+
+    /// Accumulates the area from both ends of the range, always growing the smaller one
+    fn defuzzify(&self, aggregated: &AggregatedSet) -> f64 {
+        let Some(samples) = samples(aggregated, self.resolution) else {
+            return f64::NAN;
+        };
+        let (mut left, mut right) = (0, samples.len());
+        let (mut left_area, mut right_area) = (0.0, 0.0);
+        while left < right {
+            if left_area <= right_area {
+                left_area += samples[left].1;
+                left += 1;
+            } else {
+                right -= 1;
+                right_area += samples[right].1;
+            }
+        }
+        let (x_left, x_right) = (samples[left - 1].0, samples[right.min(samples.len() - 1)].0);
+        (left_area * x_right + right_area * x_left) / (left_area + right_area)
+    }
+}
+
+impl Defuzzifier for MeanOfMaximum {
+    fn name(&self) -> &'static str {
+        "MeanOfMaximum"
+    }
+
+    fn resolution(&self) -> Option<usize> {
+        Some(self.resolution)
+    }
+
+    fn defuzzify(&self, aggregated: &AggregatedSet) -> f64 {
+        maximum(aggregated, self.resolution).map_or(f64::NAN, |(smallest, plateau_end, _)| {
+            (smallest + plateau_end) / 2.0
+        })
+    }
+}
+
+impl Defuzzifier for SmallestOfMaximum {
+    fn name(&self) -> &'static str {
+        "SmallestOfMaximum"
+    }
+
+    fn resolution(&self) -> Option<usize> {
+        Some(self.resolution)
+    }
+
+    fn defuzzify(&self, aggregated: &AggregatedSet) -> f64 {
+        maximum(aggregated, self.resolution).map_or(f64::NAN, |(smallest, _, _)| smallest)
+    }
+}
+
+impl Defuzzifier for LargestOfMaximum {
+    fn name(&self) -> &'static str {
+        "LargestOfMaximum"
+    }
+
+    fn resolution(&self) -> Option<usize> {
+        Some(self.resolution)
+    }
+
+    fn defuzzify(&self, aggregated: &AggregatedSet) -> f64 {
+        maximum(aggregated, self.resolution).map_or(f64::NAN, |(_, _, largest)| largest)
+    }
+}
+
+/// Total firing strength of the conclusions and the sum of their values weighted by it
+fn weighted(aggregated: &AggregatedSet) -> (f64, f64) {
+    aggregated
+        .conclusions
+        .iter()
+        .fold((0.0, 0.0), |(weights, sum), conclusion| {
+            let value = conclusion.set.function.evaluate_inputs(&aggregated.inputs);
+            (
+                weights + conclusion.strength,
+                sum + conclusion.strength * value,
+            )
+        })
+}
+
+impl Defuzzifier for WeightedAverage {
+    fn name(&self) -> &'static str {
+        "WeightedAverage"
+    }
+
+    fn resolution(&self) -> Option<usize> {
+        None
+    }
+
+    fn defuzzify(&self, aggregated: &AggregatedSet) -> f64 {
+        let (weights, sum) = weighted(aggregated);
+        sum / weights
+    }
+}
+
+impl Defuzzifier for WeightedSum {
+    fn name(&self) -> &'static str {
+        "WeightedSum"
+    }
+
+    fn resolution(&self) -> Option<usize> {
+        None
+    }
+
+    fn defuzzify(&self, aggregated: &AggregatedSet) -> f64 {
+        match weighted(aggregated) {
+            (0.0, _) => f64::NAN,
+            (_, sum) => sum,
+        }
+    }
+}
+
+/// Defuzzifier named as in fuzzylite, `Centroid`, `MeanOfMaximum`, ...
+/// `resolution` only applies to integral defuzzifiers and defaults to [`DEFAULT_RESOLUTION`]
+pub fn defuzzifier(name: &str, resolution: Option<usize>) -> Option<Rc<dyn Defuzzifier>> {
+    let resolution = resolution.unwrap_or(DEFAULT_RESOLUTION);
+    Some(match name {
+        "Centroid" => Rc::new(Centroid { resolution }),
+        "Bisector" => Rc::new(Bisector { resolution }),
+        "MeanOfMaximum" => Rc::new(MeanOfMaximum { resolution }),
+        "SmallestOfMaximum" => Rc::new(SmallestOfMaximum { resolution }),
+        "LargestOfMaximum" => Rc::new(LargestOfMaximum { resolution }),
+        "WeightedAverage" => Rc::new(WeightedAverage),
+        "WeightedSum" => Rc::new(WeightedSum),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzzy_logic::inference::Activated;
+    use crate::fuzzy_logic::norms::{Maximum, Minimum};
+    use crate::fuzzy_logic::{FuzzySet, MembershipFunction};
+
+    /// Conclusions over `[0, 10]` clipped at their strength
+    fn aggregated(conclusions: &[(MembershipFunction, f64)]) -> AggregatedSet {
+        AggregatedSet {
+            range: Some((0.0, 10.0)),
+            inputs: vec![2.0],
+            conclusions: conclusions
+                .iter()
+                .map(|(function, strength)| Activated {
+                    set: FuzzySet::with_function("term", function.clone()),
+                    strength: *strength,
+                    implication: Rc::new(Minimum),
+                    accumulation: Rc::new(Maximum),
+                })
+                .collect(),
+        }
+    }
+
+    fn defuzzify(name: &str, aggregated: &AggregatedSet) -> f64 {
+        defuzzifier(name, Some(1000)).unwrap().defuzzify(aggregated)
+    }
+
+    #[test]
+    fn test_symmetric() {
+        let triangle = MembershipFunction::triangular(2.0, 5.0, 8.0).unwrap();
+        let set = aggregated(&[(triangle, 1.0)]);
+        for name in ["Centroid", "Bisector", "MeanOfMaximum"] {
+            let value = defuzzify(name, &set);
+            assert!((value - 5.0).abs() < 1e-2, "{name} {value}");
+        }
+    }
+
+    #[test]
+    fn test_maximum() {
+        // Plateau of 0.5 over [2, 4] then a plateau of 1 over [6, 8]
+        let low = MembershipFunction::trapezoidal(1.0, 2.0, 4.0, 5.0).unwrap();
+        let high = MembershipFunction::trapezoidal(5.0, 6.0, 8.0, 9.0).unwrap();
+        let set = aggregated(&[(low, 0.5), (high, 1.0)]);
+        let close = |name, expected: f64| {
+            let value = defuzzify(name, &set);
+            assert!((value - expected).abs() < 1e-2, "{name} {value}");
+        };
+        close("SmallestOfMaximum", 6.0);
+        close("LargestOfMaximum", 8.0);
+        close("MeanOfMaximum", 7.0);
+        assert!(defuzzify("Centroid", &set) < 7.0);
+    }
+
+    #[test]
+    fn test_bisector() {
+        // Rectangle of 1 over [0, 2] and of 0.5 over [2, 10], with an area of 3 on each side of 4
+        let low = MembershipFunction::rectangle(0.0, 2.0).unwrap();
+        let high = MembershipFunction::rectangle(2.0, 10.0).unwrap();
+        let set = aggregated(&[(low, 1.0), (high, 0.5)]);
+        let value = defuzzify("Bisector", &set);
+        assert!((value - 4.0).abs() < 1e-2, "{value}");
+    }
+
+    #[test]
+    fn test_weighted() {
+        let constant = MembershipFunction::constant(10.0).unwrap();
+        let linear = MembershipFunction::linear(&[2.0], 1.0).unwrap();
+        let set = aggregated(&[(constant, 0.25), (linear, 0.75)]);
+        assert_eq!(defuzzify("WeightedAverage", &set), 6.25);
+        assert_eq!(defuzzify("WeightedSum", &set), 6.25);
+        let set = aggregated(&[(MembershipFunction::constant(10.0).unwrap(), 0.5)]);
+        assert_eq!(defuzzify("WeightedAverage", &set), 10.0);
+        assert_eq!(defuzzify("WeightedSum", &set), 5.0);
+    }
+
+    #[test]
+    fn test_nothing_fires() {
+        let triangle = MembershipFunction::triangular(2.0, 5.0, 8.0).unwrap();
+        let mut set = aggregated(&[(triangle, 0.0)]);
+        for name in [
+            "Centroid",
+            "Bisector",
+            "MeanOfMaximum",
+            "SmallestOfMaximum",
+            "LargestOfMaximum",
+            "WeightedAverage",
+            "WeightedSum",
+        ] {
+            assert!(defuzzify(name, &set).is_nan(), "{name}");
+        }
+        set.conclusions[0].strength = 1.0;
+        set.range = None;
+        assert!(defuzzify("Centroid", &set).is_nan());
+        assert!(defuzzifier("Unknown", None).is_none());
+    }
+}
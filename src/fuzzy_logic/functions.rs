@@ -1,34 +1,18 @@
-/// Trapezoidal distribution function
+use super::MembershipFunction;
+
+/// Triangular distribution function
 pub fn triangular(a: f64, b: f64, c: f64) -> Box<dyn Fn(f64) -> f64> {
-    Box::new(move |x| {
-        if x <= a || x >= c {
-            0.0
-        } else if x <= b {
-            (x - a) / (b - a)
-        } else {
-            (c - x) / (c - b)
-        }
-    })
+    boxed(MembershipFunction::Triangular { a, b, c })
 }
 
 /// Trapezoidal distribution function
 pub fn trapezoidal(a: f64, b: f64, c: f64, d: f64) -> Box<dyn Fn(f64) -> f64> {
-    Box::new(move |x| {
-        if x <= a || x >= d {
-            0.0
-        } else if x <= b {
-            (x - a) / (b - a)
-        } else if x <= c {
-            1.0
-        } else {
-            (d - x) / (d - c)
-        }
-    })
+    boxed(MembershipFunction::Trapezoidal { a, b, c, d })
 }
 
 /// Gaussian distribution
 pub fn gaussian(c: f64, sigma: f64) -> Box<dyn Fn(f64) -> f64> {
-    Box::new(move |x| (-((x - c).powi(2)) / (2.0 * sigma.powi(2))).exp())
+    boxed(MembershipFunction::Gaussian { c, sigma })
 }
 
 /// Sigmoid function to evaluate an x
@@ -42,17 +26,131 @@ pub fn gaussian(c: f64, sigma: f64) -> Box<dyn Fn(f64) -> f64> {
 /// # }
 /// ```
 pub fn sigmoid(a: f64, c: f64) -> Box<dyn Fn(f64) -> f64> {
-    Box::new(move |x| 1.0 / (1.0 + (-a * (x - c)).exp()))
+    boxed(MembershipFunction::Sigmoid { a, c })
 }
 
 /// Bell distribution
 pub fn bell(a: f64, b: f64, c: f64) -> Box<dyn Fn(f64) -> f64> {
-    Box::new(move |x| 1.0 / (1.0 + ((x - c) / a).abs().powf(2.0 * b)))
+    boxed(MembershipFunction::Bell { a, b, c })
+}
+
+/// Piecewise linear function interpolating between `points` sorted by `x`.
+/// The values of the first and last points are held outside of the points and repeating an `x`
+/// makes a vertical step, see [`interpolate`](super::membership::interpolate).
+/// This is the `Discrete` term of fuzzylite
+pub fn piecewise_linear(points: &[(f64, f64)]) -> Box<dyn Fn(f64) -> f64> {
+    boxed(MembershipFunction::PiecewiseLinear {
+        points: points.into(),
+    })
+}
+
+/// Ramp rising from `start` to `end`, or falling when `start > end`
+pub fn ramp(start: f64, end: f64) -> Box<dyn Fn(f64) -> f64> {
+    boxed(MembershipFunction::Ramp { start, end })
+}
+
+/// Rectangle of membership 1 between `start` and `end`
+pub fn rectangle(start: f64, end: f64) -> Box<dyn Fn(f64) -> f64> {
+    boxed(MembershipFunction::Rectangle { start, end })
+}
+
+/// S-shaped rise from `start` to `end`
+pub fn s_shape(start: f64, end: f64) -> Box<dyn Fn(f64) -> f64> {
+    boxed(MembershipFunction::SShape { start, end })
+}
+
+/// Z-shaped fall from `start` to `end`
+pub fn z_shape(start: f64, end: f64) -> Box<dyn Fn(f64) -> f64> {
+    boxed(MembershipFunction::ZShape { start, end })
+}
+
+/// Pi-shaped function, S-shaped then Z-shaped
+pub fn pi_shape(
+    bottom_left: f64,
+    top_left: f64,
+    top_right: f64,
+    bottom_right: f64,
+) -> Box<dyn Fn(f64) -> f64> {
+    boxed(MembershipFunction::PiShape {
+        bottom_left,
+        top_left,
+        top_right,
+        bottom_right,
+    })
+}
+
+/// Absolute difference of two sigmoids
+pub fn sigmoid_difference(
+    left: f64,
+    rising: f64,
+    falling: f64,
+    right: f64,
+) -> Box<dyn Fn(f64) -> f64> {
+    boxed(MembershipFunction::SigmoidDifference {
+        left,
+        rising,
+        falling,
+        right,
+    })
+}
+
+/// Product of two sigmoids
+pub fn sigmoid_product(
+    left: f64,
+    rising: f64,
+    falling: f64,
+    right: f64,
+) -> Box<dyn Fn(f64) -> f64> {
+    boxed(MembershipFunction::SigmoidProduct {
+        left,
+        rising,
+        falling,
+        right,
+    })
+}
+
+/// Product of the outer halves of two gaussians
+pub fn gaussian_product(
+    mean_a: f64,
+    standard_deviation_a: f64,
+    mean_b: f64,
+    standard_deviation_b: f64,
+) -> Box<dyn Fn(f64) -> f64> {
+    boxed(MembershipFunction::GaussianProduct {
+        mean_a,
+        standard_deviation_a,
+        mean_b,
+        standard_deviation_b,
+    })
+}
+
+/// Cosine bump of `width` centered on `center`
+pub fn cosine(center: f64, width: f64) -> Box<dyn Fn(f64) -> f64> {
+    boxed(MembershipFunction::Cosine { center, width })
 }
 
-/// Center of gravity
-fn cog(x: f64, y: f64) -> f64 {
-    x * y
+/// Concave function reaching 1 at `end`
+pub fn concave(inflection: f64, end: f64) -> Box<dyn Fn(f64) -> f64> {
+    boxed(MembershipFunction::Concave { inflection, end })
+}
+
+/// Exponential spike centered on `center`
+pub fn spike(center: f64, width: f64) -> Box<dyn Fn(f64) -> f64> {
+    boxed(MembershipFunction::Spike { center, width })
+}
+
+/// Step to 1 from `start` towards `direction`
+pub fn binary(start: f64, direction: f64) -> Box<dyn Fn(f64) -> f64> {
+    boxed(MembershipFunction::Binary { start, direction })
+}
+
+/// Constant `value`
+pub fn constant(value: f64) -> Box<dyn Fn(f64) -> f64> {
+    boxed(MembershipFunction::Constant { value })
+}
+
+fn boxed(function: MembershipFunction) -> Box<dyn Fn(f64) -> f64> {
+    Box::new(move |x| function.evaluate(x))
 }
 
 #[cfg(test)]
@@ -75,7 +173,16 @@ mod tests {
     }
 
     #[test]
-    fn test_cog() {
-        assert_eq!(cog(1.0, 1.0), 1.0);
+    fn test_piecewise_linear() {
+        let trapezoid = piecewise_linear(&[(0.0, 0.0), (20.0, 1.0), (40.0, 1.0), (60.0, 0.0)]);
+        assert_eq!(trapezoid(-10.0), 0.0);
+        assert_eq!(trapezoid(10.0), 0.5);
+        assert_eq!(trapezoid(30.0), 1.0);
+        assert_eq!(trapezoid(55.0), 0.25);
+        assert_eq!(trapezoid(70.0), 0.0);
+
+        let shoulder = piecewise_linear(&[(-20.0, 1.0), (-10.0, 1.0), (0.0, 0.0)]);
+        assert_eq!(shoulder(-30.0), 1.0);
+        assert_eq!(shoulder(10.0), 0.0);
     }
 }
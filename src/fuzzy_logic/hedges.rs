@@ -0,0 +1,87 @@
+//! Hedges modifying the membership of a term in a rule condition, `very`, `somewhat`, ...
+//!
+//! The implementations and their names follow fuzzylite, `not` is the negation of a condition.
+use std::rc::Rc;
+
+/// Modifier of a membership in `[0, 1]`
+pub trait Hedge {
+    fn name(&self) -> &'static str;
+    fn apply(&self, x: f64) -> f64;
+}
+
+/// Concentration `x²`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Very;
+
+/// Dilation `√x`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Somewhat;
+
+/// `√(x / 2)` up to 0.5, `1 - √((1 - x) / 2)` above
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Seldom;
+
+/// Contrast intensification, `2 x²` up to 0.5, `1 - 2 (1 - x)²` above
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Extremely;
+
+macro_rules! hedge {
+    ($hedge:ident, $name:literal, |$x:ident| $apply:expr) => {
+        impl Hedge for $hedge {
+            fn name(&self) -> &'static str {
+                $name
+            }
+
+            fn apply(&self, $x: f64) -> f64 {
+                $apply
+            }
+        }
+    };
+}
+
+hedge!(Very, "very", |x| x * x);
+hedge!(Somewhat, "somewhat", |x| x.sqrt());
+hedge!(Seldom, "seldom", |x| if x <= 0.5 {
+    (0.5 * x).sqrt()
+} else {
+    1.0 - (0.5 * (1.0 - x)).sqrt()
+});
+hedge!(Extremely, "extremely", |x| if x <= 0.5 {
+    2.0 * x * x
+} else {
+    1.0 - 2.0 * (1.0 - x) * (1.0 - x)
+});
+
+/// Hedge named as in fuzzylite, `very`, `somewhat`, `seldom` or `extremely`
+pub fn hedge(name: &str) -> Option<Rc<dyn Hedge>> {
+    Some(match name {
+        "very" => Rc::new(Very),
+        "somewhat" => Rc::new(Somewhat),
+        "seldom" => Rc::new(Seldom),
+        "extremely" => Rc::new(Extremely),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hedges() {
+        for name in ["very", "somewhat", "seldom", "extremely"] {
+            let hedge = hedge(name).unwrap();
+            assert_eq!(hedge.name(), name);
+            assert_eq!(hedge.apply(0.0), 0.0, "{name}(0)");
+            assert_eq!(hedge.apply(1.0), 1.0, "{name}(1)");
+        }
+        let apply = |name, x| hedge(name).unwrap().apply(x);
+        assert_eq!(apply("very", 0.5), 0.25);
+        assert_eq!(apply("somewhat", 0.25), 0.5);
+        assert_eq!(apply("seldom", 0.5), 0.5);
+        assert_eq!(apply("seldom", 0.125), 0.25);
+        assert_eq!(apply("extremely", 0.25), 0.125);
+        assert_eq!(apply("extremely", 0.75), 0.875);
+        assert!(hedge("not").is_none());
+    }
+}
@@ -1,7 +1,447 @@
+use std::ops::Index;
+
+use crate::fuzzy_logic::{FuzzyVariable, MembershipFunction, Proposition, RuleBlock};
+
+pub mod aggregated;
+pub mod error;
 pub mod mamdani;
+pub mod sugeno;
+pub mod trace;
+pub mod tsukamoto;
+pub use aggregated::*;
+pub use error::InferenceError;
 pub use mamdani::*;
+pub use sugeno::*;
+pub use trace::*;
+pub use tsukamoto::*;
 
 pub trait FuzzySystem {
     fn fuzzify(&self, input_values: &[f64]) -> Vec<Vec<f64>>;
-    fn infer(&self, input_values: &[f64]) -> f64;
+
+    /// Crisp value of each output from its aggregated set, in the order of the outputs, its
+    /// default when no rule fires on it
+    fn defuzzify_outputs(&self, aggregated: Vec<AggregatedSet>) -> Outputs;
+
+    fn inputs(&self) -> &[FuzzyVariable];
+
+    fn outputs(&self) -> &[FuzzyVariable];
+
+    fn rule_blocks(&self) -> &[RuleBlock];
+
+    /// Crisp value of every output variable, its default when no rule fires on it
+    fn infer_outputs(&self, input_values: &[f64]) -> Outputs {
+        let aggregated = aggregate(self.inputs(), self.outputs(), self.rule_blocks(), input_values);
+        self.defuzzify_outputs(aggregated)
+    }
+
+    /// Crisp value of the first output variable, see [`FuzzySystem::infer_outputs`]
+    fn infer(&self, input_values: &[f64]) -> f64 {
+        self.infer_outputs(input_values)
+            .values
+            .first()
+            .map_or(f64::NAN, |&(_, value)| value)
+    }
+
+    /// Checks that there is a finite value for each input, within the range of its variable
+    /// under [`RangePolicy::Strict`]
+    fn check_inputs(
+        &self,
+        input_values: &[f64],
+        policy: RangePolicy,
+    ) -> Result<(), InferenceError> {
+        check_inputs(self.inputs(), input_values, policy)
+    }
+
+    /// Crisp value of every output variable of checked inputs, see
+    /// [`FuzzySystem::check_inputs`]
+    fn try_infer_outputs(
+        &self,
+        input_values: &[f64],
+        policy: RangePolicy,
+    ) -> Result<Outputs, InferenceError> {
+        self.check_inputs(input_values, policy)?;
+        Ok(self.infer_outputs(input_values))
+    }
+
+    /// Crisp value of the first output variable of checked inputs, see
+    /// [`FuzzySystem::check_inputs`]
+    fn try_infer(&self, input_values: &[f64], policy: RangePolicy) -> Result<f64, InferenceError> {
+        self.check_inputs(input_values, policy)?;
+        Ok(self.infer(input_values))
+    }
+
+    /// Crisp value of every output variable with the trace of the inference explaining them
+    fn infer_traced(&self, input_values: &[f64]) -> (Outputs, Trace) {
+        Trace::new(
+            self.inputs(),
+            self.outputs(),
+            self.rule_blocks(),
+            input_values,
+            |aggregated| self.defuzzify_outputs(aggregated),
+        )
+    }
+}
+
+/// How the inputs outside of the range of their variable are checked, see
+/// [`FuzzySystem::check_inputs`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RangePolicy {
+    /// Inputs are clamped when their variable locks its range and used as they are otherwise
+    #[default]
+    Lenient,
+    /// Inputs outside of the range of their variable are rejected
+    Strict,
+}
+
+/// Crisp values of the output variables of a system, in the order of the variables
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Outputs {
+    values: Vec<(String, f64)>,
+    /// Outputs no rule fired on
+    defaulted: Vec<String>,
+}
+
+impl Outputs {
+    /// Value of the output variable named `name`
+    pub fn get(&self, name: &str) -> Option<f64> {
+        self.iter()
+            .find(|&(output, _)| output == name)
+            .map(|(_, value)| value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, f64)> {
+        self.values
+            .iter()
+            .map(|(name, value)| (name.as_str(), *value))
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Whether no rule fired on the output named `name`, which took its default value
+    pub fn is_default(&self, name: &str) -> bool {
+        self.defaulted.iter().any(|output| output == name)
+    }
+
+    /// Moves the outputs of `other` after these, leaving it empty
+    pub fn append(&mut self, other: &mut Outputs) {
+        self.values.append(&mut other.values);
+        self.defaulted.append(&mut other.defaulted);
+    }
+
+    /// Sets the outputs of `variables` locking their previous value that no rule fired on to
+    /// their value in `previous`, unless it is NaN. The outputs set are no longer defaults
+    pub fn lock_previous(&mut self, variables: &[FuzzyVariable], previous: &Outputs) {
+        for (name, value) in &mut self.values {
+            let locked = variables
+                .iter()
+                .any(|variable| variable.name == *name && variable.lock_previous);
+            if !locked || !self.defaulted.contains(name) {
+                continue;
+            }
+            if let Some(previous) = previous.get(name).filter(|previous| !previous.is_nan()) {
+                *value = previous;
+                self.defaulted.retain(|output| output != name);
+            }
+        }
+    }
+}
+
+impl FromIterator<(String, f64)> for Outputs {
+    fn from_iter<I: IntoIterator<Item = (String, f64)>>(iter: I) -> Self {
+        Self {
+            values: iter.into_iter().collect(),
+            defaulted: Vec::new(),
+        }
+    }
+}
+
+/// Panics when the system has no output variable named `name`
+impl Index<&str> for Outputs {
+    type Output = f64;
+
+    fn index(&self, name: &str) -> &f64 {
+        self.values
+            .iter()
+            .find(|(output, _)| output == name)
+            .map(|(_, value)| value)
+            .unwrap_or_else(|| panic!("no output variable {name}"))
+    }
+}
+
+/// Checks that there is an output and a rule, that the variables have distinct names and that
+/// the rules name them and their terms
+fn validate(
+    inputs: &[FuzzyVariable],
+    outputs: &[FuzzyVariable],
+    rule_blocks: &[RuleBlock],
+) -> Result<(), InferenceError> {
+    let unit = 0.0..=1.0;
+    if outputs.is_empty() {
+        return Err(InferenceError::NoOutput);
+    }
+    let variables: Vec<_> = inputs.iter().chain(outputs).collect();
+    for (index, variable) in variables.iter().enumerate() {
+        if variables[..index]
+            .iter()
+            .any(|other| other.name == variable.name)
+        {
+            return Err(InferenceError::DuplicateVariable {
+                variable: variable.name.clone(),
+            });
+        }
+    }
+    let find = |variables: &'_ [FuzzyVariable], name: &str| {
+        variables.iter().position(|variable| variable.name == name)
+    };
+    if rule_blocks.iter().all(|block| block.rules.is_empty()) {
+        return Err(InferenceError::EmptyRuleBase);
+    }
+    for block in rule_blocks {
+        if let Some(threshold) = block.activation.threshold() {
+            if !unit.contains(&threshold) {
+                return Err(InferenceError::InvalidThreshold {
+                    block: block.name.clone(),
+                    activation: block.activation.name().to_string(),
+                    threshold,
+                });
+            }
+        }
+        for (index, rule) in block.rules.iter().enumerate() {
+            let number = index + 1;
+            if !unit.contains(&rule.weight) {
+                return Err(InferenceError::InvalidWeight {
+                    block: block.name.clone(),
+                    rule: number,
+                    weight: rule.weight,
+                });
+            }
+            let propositions = rule.condition.propositions();
+            if propositions.is_empty() {
+                return Err(InferenceError::NoAntecedent {
+                    block: block.name.clone(),
+                    rule: number,
+                });
+            }
+            let unresolved = |variable: &str, input| InferenceError::UnresolvedVariable {
+                block: block.name.clone(),
+                rule: number,
+                variable: variable.to_string(),
+                input,
+            };
+            for proposition in propositions {
+                let input = find(inputs, &proposition.variable)
+                    .ok_or_else(|| unresolved(&proposition.variable, true))?;
+                check_term(&inputs[input], proposition, number, &block.name)?;
+            }
+            for consequent in &rule.consequents {
+                let output = find(outputs, &consequent.variable)
+                    .ok_or_else(|| unresolved(&consequent.variable, false))?;
+                check_term(&outputs[output], consequent, number, &block.name)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the terms of `output`, defuzzified by a weighted defuzzifier, are constant or
+/// linear with a coefficient per input
+fn check_weighted(inputs: &[FuzzyVariable], output: &FuzzyVariable) -> Result<(), InferenceError> {
+    for set in &output.fuzzy_sets {
+        match set.function {
+            MembershipFunction::Constant { .. } => {}
+            MembershipFunction::Linear {
+                ref coefficients, ..
+            } if coefficients.len() != inputs.len() => {
+                return Err(InferenceError::CoefficientCount {
+                    output: output.name.clone(),
+                    term: set.name.clone(),
+                    coefficients: coefficients.len(),
+                    inputs: inputs.len(),
+                })
+            }
+            MembershipFunction::Linear { .. } => {}
+            ref function => {
+                return Err(InferenceError::NotConstantOrLinear {
+                    output: output.name.clone(),
+                    term: set.name.clone(),
+                    function: function.name().to_string(),
+                })
+            }
+        }
+    }
+    Ok(())
+}
+
+fn check_term(
+    variable: &FuzzyVariable,
+    proposition: &Proposition,
+    number: usize,
+    block: &str,
+) -> Result<(), InferenceError> {
+    if variable.set(&proposition.term).is_none() {
+        return Err(InferenceError::UnresolvedTerm {
+            block: block.to_string(),
+            rule: number,
+            variable: variable.name.clone(),
+            term: proposition.term.clone(),
+        });
+    }
+    Ok(())
+}
+
+fn check_inputs(
+    inputs: &[FuzzyVariable],
+    input_values: &[f64],
+    policy: RangePolicy,
+) -> Result<(), InferenceError> {
+    if input_values.len() != inputs.len() {
+        return Err(InferenceError::Arity {
+            expected: inputs.len(),
+            found: input_values.len(),
+        });
+    }
+    for (input, &value) in inputs.iter().zip(input_values) {
+        if !value.is_finite() {
+            return Err(InferenceError::NotFinite {
+                variable: input.name.clone(),
+                value,
+            });
+        }
+        match input.range() {
+            Some((min, max)) if policy == RangePolicy::Strict && !(min..=max).contains(&value) => {
+                return Err(InferenceError::OutOfRange {
+                    variable: input.name.clone(),
+                    value,
+                    min,
+                    max,
+                })
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Membership of each input value, clamped when its variable locks its range, to each term
+fn fuzzify(inputs: &[FuzzyVariable], input_values: &[f64]) -> Vec<Vec<f64>> {
+    debug_assert_eq!(input_values.len(), inputs.len(), "one value per input variable");
+    inputs
+        .iter()
+        .zip(input_values)
+        .map(|(input_var, &input_value)| {
+            input_var
+                .fuzzy_sets
+                .iter()
+                .map(|fuzzy_set| fuzzy_set.membership(input_var.clamp(input_value)))
+                .collect()
+        })
+        .collect()
+}
+
+/// Fuzzy set of each output variable, in their order, gathering the conclusions of every rule
+/// activated by `input_values`
+fn aggregate(
+    inputs: &[FuzzyVariable],
+    outputs: &[FuzzyVariable],
+    rule_blocks: &[RuleBlock],
+    input_values: &[f64],
+) -> Vec<AggregatedSet> {
+    // Fuzzify the input values
+    let fuzzy_inputs = fuzzify(inputs, input_values);
+    let clamped: Vec<_> = inputs
+        .iter()
+        .zip(input_values)
+        .map(|(input, &value)| input.clamp(value))
+        .collect();
+    let mut aggregated: Vec<_> = outputs
+        .iter()
+        .map(|output| AggregatedSet {
+            range: output.range(),
+            inputs: clamped.clone(),
+            conclusions: Vec::new(),
+        })
+        .collect();
+
+    // Apply the rules activated by their block to get their conclusions on each output
+    for block in rule_blocks {
+        let strengths: Vec<_> = block
+            .rules
+            .iter()
+            .map(|rule| {
+                rule.activation(block, &|proposition| {
+                    truth(inputs, &fuzzy_inputs, proposition)
+                })
+            })
+            .collect();
+        let activated = block.activation.activate(&strengths);
+        for (rule, degree) in block.rules.iter().zip(activated) {
+            let Some(strength) = degree else {
+                continue;
+            };
+            for consequent in &rule.consequents {
+                let Some(output) = outputs
+                    .iter()
+                    .position(|output| output.name == consequent.variable)
+                else {
+                    continue;
+                };
+                let Some(set) = outputs[output].set(&consequent.term) else {
+                    continue;
+                };
+                aggregated[output].conclusions.push(Activated {
+                    set: set.clone(),
+                    strength,
+                    implication: block.implication.clone(),
+                    accumulation: block.accumulation.clone(),
+                });
+            }
+        }
+    }
+
+    aggregated
+}
+
+/// Crisp value of each output from its aggregated set, its default when no rule fires on it
+fn defuzzify(
+    outputs: &[FuzzyVariable],
+    aggregated: Vec<AggregatedSet>,
+    defuzzify: impl Fn(&FuzzyVariable, &AggregatedSet) -> f64,
+) -> Outputs {
+    let mut crisp = Outputs::default();
+    for (output, aggregated) in outputs.iter().zip(aggregated) {
+        let value = if aggregated.fired() {
+            defuzzify(output, &aggregated)
+        } else {
+            crisp.defaulted.push(output.name.clone());
+            output.default
+        };
+        crisp
+            .values
+            .push((output.name.clone(), output.clamp(value)));
+    }
+    crisp
+}
+
+/// Membership of the input tested by `proposition` to its term, resolved when building the
+/// system
+fn truth(inputs: &[FuzzyVariable], fuzzy_inputs: &[Vec<f64>], proposition: &Proposition) -> f64 {
+    let input = inputs
+        .iter()
+        .position(|input| input.name == proposition.variable);
+    let set = input.and_then(|input| {
+        inputs[input]
+            .fuzzy_sets
+            .iter()
+            .position(|set| set.name == proposition.term)
+    });
+    match (input, set) {
+        (Some(input), Some(set)) => fuzzy_inputs[input][set],
+        _ => 0.0,
+    }
 }
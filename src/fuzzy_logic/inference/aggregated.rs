@@ -0,0 +1,48 @@
+use std::rc::Rc;
+
+use crate::fuzzy_logic::{FuzzySet, SNorm, TNorm};
+
+/// Conclusion of a rule, its consequent modified by the implication at the firing strength
+#[derive(Clone)]
+pub struct Activated {
+    pub set: FuzzySet,
+    pub strength: f64,
+    pub implication: Rc<dyn TNorm>,
+    /// S-norm of the rule block accumulating the conclusion into the output
+    pub accumulation: Rc<dyn SNorm>,
+}
+
+impl Activated {
+    pub fn membership(&self, x: f64) -> f64 {
+        self.implication
+            .compute(self.strength, self.set.membership(x))
+    }
+}
+
+/// Output fuzzy set accumulating the conclusions of the rules over the range of the output
+#[derive(Clone)]
+pub struct AggregatedSet {
+    /// Range of the output variable, `None` when it has none
+    pub range: Option<(f64, f64)>,
+    /// Crisp inputs of the system, which linear conclusions depend on
+    pub inputs: Vec<f64>,
+    pub conclusions: Vec<Activated>,
+}
+
+impl AggregatedSet {
+    /// Whether a rule concludes with a positive strength
+    pub fn fired(&self) -> bool {
+        self.conclusions
+            .iter()
+            .any(|conclusion| conclusion.strength > 0.0)
+    }
+
+    /// Membership of `x`, 0 when no rule concludes
+    pub fn membership(&self, x: f64) -> f64 {
+        self.conclusions.iter().fold(0.0, |membership, conclusion| {
+            conclusion
+                .accumulation
+                .compute(membership, conclusion.membership(x))
+        })
+    }
+}
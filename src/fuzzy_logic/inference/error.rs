@@ -0,0 +1,195 @@
+use std::fmt;
+
+/// Problem of a system found when building it or of the inputs of an inference, see
+/// [`FuzzySystem::try_infer`]
+///
+/// [`FuzzySystem::try_infer`]: super::FuzzySystem::try_infer
+#[derive(Debug, Clone, PartialEq)]
+pub enum InferenceError {
+    /// The number of input values differs from the number of input variables
+    Arity { expected: usize, found: usize },
+    /// An input value is NaN or infinite
+    NotFinite { variable: String, value: f64 },
+    /// An input value is outside of the range of its variable, under [`RangePolicy::Strict`]
+    ///
+    /// [`RangePolicy::Strict`]: super::RangePolicy::Strict
+    OutOfRange {
+        variable: String,
+        value: f64,
+        min: f64,
+        max: f64,
+    },
+    /// The system has no output variable
+    NoOutput,
+    /// Two variables of the system share a name
+    DuplicateVariable { variable: String },
+    /// The threshold of the activation of a rule block is outside of `[0, 1]`
+    InvalidThreshold {
+        block: String,
+        activation: String,
+        threshold: f64,
+    },
+    /// The weight of a rule is outside of `[0, 1]`
+    InvalidWeight {
+        block: String,
+        rule: usize,
+        weight: f64,
+    },
+    /// A rule tests a variable that is not an input, or concludes on one that is not an output
+    UnresolvedVariable {
+        block: String,
+        rule: usize,
+        variable: String,
+        /// Whether the rule tests the variable rather than concludes on it
+        input: bool,
+    },
+    /// A rule names a term its variable does not have
+    UnresolvedTerm {
+        block: String,
+        rule: usize,
+        variable: String,
+        term: String,
+    },
+    /// A rule has no antecedent
+    NoAntecedent { block: String, rule: usize },
+    /// No rule block has a rule
+    EmptyRuleBase,
+    /// A Sugeno output is not defuzzified by `WeightedAverage` or `WeightedSum`
+    WrongDefuzzifier { output: String, defuzzifier: String },
+    /// A term of a Sugeno output, or of an output defuzzified by `WeightedAverage` or
+    /// `WeightedSum`, is neither `Constant` nor `Linear`
+    NotConstantOrLinear {
+        output: String,
+        term: String,
+        function: String,
+    },
+    /// A `Linear` term does not have a coefficient per input variable
+    CoefficientCount {
+        output: String,
+        term: String,
+        coefficients: usize,
+        inputs: usize,
+    },
+    /// A Tsukamoto output has no range to bound the values its terms invert to
+    NoRange { output: String },
+    /// A term of a Tsukamoto output is not monotonic, see [`MembershipFunction::is_monotonic`]
+    ///
+    /// [`MembershipFunction::is_monotonic`]: crate::fuzzy_logic::MembershipFunction::is_monotonic
+    NotMonotonic {
+        output: String,
+        term: String,
+        function: String,
+    },
+}
+
+impl fmt::Display for InferenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Arity { expected, found } => {
+                write!(f, "{found} input values for {expected} input variables")
+            }
+            Self::NotFinite { variable, value } => write!(f, "input {variable} is {value}"),
+            Self::OutOfRange {
+                variable,
+                value,
+                min,
+                max,
+            } => write!(
+                f,
+                "input {variable} is {value}, outside of its range [{min}, {max}]"
+            ),
+            Self::NoOutput => write!(f, "the system has no output variable"),
+            Self::DuplicateVariable { variable } => {
+                write!(f, "variable {variable} is declared twice")
+            }
+            Self::InvalidThreshold {
+                block,
+                activation,
+                threshold,
+            } => write!(
+                f,
+                "activation {activation} of {block} has a threshold of {threshold}, outside of \
+                 [0, 1]"
+            ),
+            Self::InvalidWeight {
+                block,
+                rule,
+                weight,
+            } => write!(
+                f,
+                "rule {rule} of {block} has a weight of {weight}, outside of [0, 1]"
+            ),
+            Self::UnresolvedVariable {
+                block,
+                rule,
+                variable,
+                input: true,
+            } => write!(
+                f,
+                "rule {rule} of {block} tests {variable}, which is not an input variable"
+            ),
+            Self::UnresolvedVariable {
+                block,
+                rule,
+                variable,
+                input: false,
+            } => write!(
+                f,
+                "rule {rule} of {block} concludes on {variable}, which is not an output variable"
+            ),
+            Self::UnresolvedTerm {
+                block,
+                rule,
+                variable,
+                term,
+            } => write!(f, "rule {rule} of {block}: {variable} has no term {term}"),
+            Self::NoAntecedent { block, rule } => {
+                write!(f, "rule {rule} of {block} has no antecedent")
+            }
+            Self::EmptyRuleBase => write!(f, "the system has no rule"),
+            Self::WrongDefuzzifier {
+                output,
+                defuzzifier,
+            } => write!(
+                f,
+                "output {output} is defuzzified by {defuzzifier}, Sugeno outputs need \
+                 WeightedAverage or WeightedSum"
+            ),
+            Self::NotConstantOrLinear {
+                output,
+                term,
+                function,
+            } => write!(
+                f,
+                "term {term} of {output} is {function}, weighted outputs need Constant or \
+                 Linear terms"
+            ),
+            Self::CoefficientCount {
+                output,
+                term,
+                coefficients,
+                inputs,
+            } => write!(
+                f,
+                "term {term} of {output} has {coefficients} coefficients for {inputs} input \
+                 variables"
+            ),
+            Self::NoRange { output } => write!(
+                f,
+                "output {output} has no range, Tsukamoto outputs need one to bound the values \
+                 their terms invert to"
+            ),
+            Self::NotMonotonic {
+                output,
+                term,
+                function,
+            } => write!(
+                f,
+                "term {term} of {output} is {function}, Tsukamoto terms are Ramp, Sigmoid, \
+                 SShape, ZShape or Concave"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for InferenceError {}
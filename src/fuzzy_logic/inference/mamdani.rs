@@ -1,92 +1,507 @@
+use super::{
+    aggregate, check_weighted, defuzzify, fuzzify, validate, AggregatedSet, FuzzySystem,
+    InferenceError, Outputs,
+};
 use crate::fuzzy_logic::*;
-use super::FuzzySystem;
 
 pub struct Mamdani {
     inputs: Vec<FuzzyVariable>,
-    output: FuzzyVariable,
-    pub rules: Vec<Rule>,
+    outputs: Vec<FuzzyVariable>,
+    rule_blocks: Vec<RuleBlock>,
 }
 
 impl Mamdani {
-    pub fn new(inputs: Vec<FuzzyVariable>, output: FuzzyVariable, rules: Vec<Rule>) -> Self {
-        Self {
-            inputs,
-            output,
-            rules,
+    /// System with a single rule block of the default norms, see [`RuleBlock::new`]
+    pub fn new(
+        inputs: Vec<FuzzyVariable>,
+        outputs: Vec<FuzzyVariable>,
+        rules: Vec<Rule>,
+    ) -> Result<Self, InferenceError> {
+        Self::with_rule_blocks(inputs, outputs, vec![RuleBlock::new("No1", rules)])
+    }
+
+    /// Fails when there is no output or no rule, when variables share a name, when a rule has
+    /// no antecedent or names a variable or a term that does not exist, or when an output
+    /// defuzzified by `WeightedAverage` or `WeightedSum` has a term that is not constant or linear
+    pub fn with_rule_blocks(
+        inputs: Vec<FuzzyVariable>,
+        outputs: Vec<FuzzyVariable>,
+        rule_blocks: Vec<RuleBlock>,
+    ) -> Result<Self, InferenceError> {
+        validate(&inputs, &outputs, &rule_blocks)?;
+        for output in &outputs {
+            // Weighted defuzzifiers take the value of each conclusion
+            if output.defuzzifier.resolution().is_none() {
+                check_weighted(&inputs, output)?;
+            }
         }
+        Ok(Self {
+            inputs,
+            outputs,
+            rule_blocks,
+        })
+    }
+
+    /// Rules of every rule block
+    pub fn rules(&self) -> impl Iterator<Item = &Rule> {
+        self.rule_blocks.iter().flat_map(|block| &block.rules)
+    }
+
+    /// Output variable named `name`
+    pub fn output(&self, name: &str) -> Option<&FuzzyVariable> {
+        self.outputs.iter().find(|output| output.name == name)
     }
 }
 
 impl FuzzySystem for Mamdani {
     // Fuzzify the inputs
     fn fuzzify(&self, input_values: &[f64]) -> Vec<Vec<f64>> {
-        self.inputs
-            .iter()
-            .zip(input_values)
-            .map(|(input_var, &input_value)| {
-                input_var
-                    .fuzzy_sets
-                    .iter()
-                    .map(|fuzzy_set| fuzzy_set.membership(input_value))
-                    .collect()
+        fuzzify(&self.inputs, input_values)
+    }
+
+    /// Defuzzifies each output with its defuzzifier, see [`Defuzzifier::defuzzify`]
+    fn defuzzify_outputs(&self, aggregated: Vec<AggregatedSet>) -> Outputs {
+        defuzzify(&self.outputs, aggregated, |output, aggregated| {
+            output.defuzzifier.defuzzify(aggregated)
+        })
+    }
+
+    fn inputs(&self) -> &[FuzzyVariable] {
+        &self.inputs
+    }
+
+    fn outputs(&self) -> &[FuzzyVariable] {
+        &self.outputs
+    }
+
+    fn rule_blocks(&self) -> &[RuleBlock] {
+        &self.rule_blocks
+    }
+}
+
+impl Mamdani {
+    /// Fuzzy set of each output variable, in their order, aggregating the conclusions of every
+    /// rule for `input_values`
+    pub fn aggregate(&self, input_values: &[f64]) -> Vec<AggregatedSet> {
+        aggregate(&self.inputs, &self.outputs, &self.rule_blocks, input_values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::fuzzy_logic::defuzzifier::WeightedAverage;
+    use crate::fuzzy_logic::hedges;
+    use crate::fuzzy_logic::inference::RangePolicy;
+    use crate::fuzzy_logic::norms::{AlgebraicProduct, BoundedSum};
+
+    /// One input and one output over `[0, 1]`, `low` implies `low` and `high` implies `high`
+    fn system(block: impl Fn(Vec<Rule>) -> RuleBlock) -> Mamdani {
+        let low = FuzzySet::from_points("low", vec![(0.0, 1.0), (1.0, 0.0)]);
+        let high = FuzzySet::from_points("high", vec![(0.0, 0.0), (1.0, 1.0)]);
+        let variable = |name| FuzzyVariable::new(name, vec![low.clone(), high.clone()]);
+        let rules = vec![
+            Rule::new(&[("in", "low")], ("out", "low")),
+            Rule::new(&[("in", "high")], ("out", "high")),
+        ];
+        Mamdani::with_rule_blocks(
+            vec![variable("in")],
+            vec![variable("out")],
+            vec![block(rules)],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_try_infer() {
+        let mamdani = system(|rules| RuleBlock::new("mamdani", rules));
+        assert_eq!(
+            mamdani.try_infer(&[0.5, 0.5], RangePolicy::Lenient),
+            Err(InferenceError::Arity {
+                expected: 1,
+                found: 2
             })
-            .collect()
-    }
-
-    /// Perform the inference process
-    fn infer(&self, input_values: &[f64]) -> f64 {
-        // Fuzzify the input values 
-        let fuzzy_inputs = self.fuzzify(input_values);
-
-        // Apply the rules to get the rule outputs
-        let rule_outputs: Vec<f64> = self
-            .rules
-            .iter()
-            .map(|rule| {
-                let min_value = rule
-                    .antecedents
-                    .iter()
-                    .enumerate()
-                    .map(|(i, apriori)| {
-                        let set_index = self.inputs[i]
-                            .fuzzy_sets
-                            .iter()
-                            .position(|fs| fs.name == apriori.name)
-                            .unwrap();
-
-                        fuzzy_inputs[i][set_index]
-                    })
-                    .min_by(|a, b| a.partial_cmp(b).unwrap())
-                    .unwrap();
-
-                rule.consequent.membership(min_value)
+        );
+        let error = mamdani
+            .try_infer(&[f64::NAN], RangePolicy::Lenient)
+            .unwrap_err();
+        assert_eq!(error.to_string(), "input in is NaN");
+        // The unchecked inference does not panic on NaN either
+        assert!(mamdani.infer(&[f64::NAN]).is_nan());
+
+        assert!(mamdani.try_infer(&[2.0], RangePolicy::Lenient).is_ok());
+        assert_eq!(
+            mamdani.try_infer(&[2.0], RangePolicy::Strict),
+            Err(InferenceError::OutOfRange {
+                variable: "in".to_string(),
+                value: 2.0,
+                min: 0.0,
+                max: 1.0
             })
-            .collect();
+        );
+        assert_eq!(
+            mamdani.try_infer(&[0.25], RangePolicy::Strict),
+            Ok(mamdani.infer(&[0.25]))
+        );
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        let variable = || FuzzyVariable::new("v", vec![FuzzySet::from_points("t", vec![])]);
+        let error = |rules: Vec<Rule>| {
+            Mamdani::new(
+                vec![variable()],
+                vec![FuzzyVariable::new("out", vec![])],
+                rules,
+            )
+            .err()
+            .unwrap()
+        };
+        assert_eq!(error(vec![]), InferenceError::EmptyRuleBase);
+        assert_eq!(
+            error(vec![Rule::new(&[], ("out", "t"))]),
+            InferenceError::NoAntecedent {
+                block: "No1".to_string(),
+                rule: 1
+            }
+        );
+        assert_eq!(
+            error(vec![Rule::new(&[("v", "u")], ("out", "t"))]),
+            InferenceError::UnresolvedTerm {
+                block: "No1".to_string(),
+                rule: 1,
+                variable: "v".to_string(),
+                term: "u".to_string()
+            }
+        );
+        assert_eq!(
+            error(vec![Rule::new(&[("out", "t")], ("out", "t"))]),
+            InferenceError::UnresolvedVariable {
+                block: "No1".to_string(),
+                rule: 1,
+                variable: "out".to_string(),
+                input: true
+            }
+        );
+        assert_eq!(
+            error(vec![Rule::new(&[("v", "t")], ("out", "t")).with_weight(2.0)]),
+            InferenceError::InvalidWeight {
+                block: "No1".to_string(),
+                rule: 1,
+                weight: 2.0
+            }
+        );
+        let twice = Mamdani::new(vec![variable()], vec![variable()], vec![]);
+        assert_eq!(
+            twice.err(),
+            Some(InferenceError::DuplicateVariable {
+                variable: "v".to_string()
+            })
+        );
 
-        // Aggregate the rule outputs
-        let aggregated_output = self
-            .output
-            .fuzzy_sets
-            .iter()
-            .map(|output_set| {
-                let max_membership = rule_outputs
-                    .iter()
-                    .map(|&rule_output| output_set.membership(rule_output))
-                    .max_by(|a, b| a.partial_cmp(b).unwrap())
-                    .unwrap();
-
-                (output_set.name.clone(), max_membership)
+        let weighted = FuzzyVariable::new("out", vec![FuzzySet::from_points("t", vec![])])
+            .with_defuzzifier(Rc::new(WeightedAverage));
+        let rules = vec![Rule::new(&[("v", "t")], ("out", "t"))];
+        assert_eq!(
+            Mamdani::new(vec![variable()], vec![weighted], rules).err(),
+            Some(InferenceError::NotConstantOrLinear {
+                output: "out".to_string(),
+                term: "t".to_string(),
+                function: "PiecewiseLinear".to_string()
             })
-            .collect::<Vec<(String, f64)>>();
+        );
+    }
+
+    #[test]
+    fn test_implication() {
+        let mamdani = system(|rules| RuleBlock::new("mamdani", rules));
+        let clipped = mamdani.aggregate(&[0.25]).remove(0);
+        assert_eq!(clipped.range, Some((0.0, 1.0)));
+        assert_eq!(clipped.membership(0.0), 0.75);
+        assert_eq!(clipped.membership(0.5), 0.5);
+        assert_eq!(clipped.membership(1.0), 0.25);
+
+        let larsen = system(|rules| {
+            RuleBlock::new("larsen", rules).with_implication(Rc::new(AlgebraicProduct))
+        });
+        let scaled = larsen.aggregate(&[0.25]).remove(0);
+        assert_eq!(scaled.membership(0.0), 0.75);
+        assert_eq!(scaled.membership(0.5), 0.375);
+        assert_eq!(scaled.membership(1.0), 0.25);
+    }
+
+    #[test]
+    fn test_infer_centroid() {
+        let mamdani = system(|rules| RuleBlock::new("mamdani", rules));
+        assert!((mamdani.infer(&[0.5]) - 0.5).abs() < 1e-12);
+        assert!(mamdani.infer(&[0.25]) < 0.5);
+        assert!(mamdani.infer(&[0.75]) > 0.5);
+        let (low, high) = (mamdani.infer(&[0.1]), mamdani.infer(&[0.9]));
+        assert!((low + high - 1.0).abs() < 1e-12, "{low} {high}");
+
+        // Only the conclusions of the rules weigh, a single rule at full strength gives the
+        // centroid of its consequent
+        let single = system(|mut rules| {
+            rules.truncate(1);
+            RuleBlock::new("single", rules)
+        });
+        assert!((single.infer(&[0.0]) - 1.0 / 3.0).abs() < 1e-4);
+        assert!(single.infer(&[1.0]).is_nan());
+    }
+
+    #[test]
+    fn test_dont_care_inputs() {
+        let low = FuzzySet::from_points("low", vec![(0.0, 1.0), (1.0, 0.0)]);
+        let high = FuzzySet::from_points("high", vec![(0.0, 0.0), (1.0, 1.0)]);
+        let variable = |name| FuzzyVariable::new(name, vec![low.clone(), high.clone()]);
+        let system = |rules| {
+            let inputs = vec![variable("a"), variable("b"), variable("c")];
+            Mamdani::new(inputs, vec![variable("out")], rules)
+        };
+
+        let mamdani = system(vec![
+            Rule::new(&[("b", "low")], ("out", "low")),
+            Rule::new(&[("c", "high"), ("a", "high")], ("out", "high")),
+        ])
+        .unwrap();
+        let aggregated = mamdani.aggregate(&[0.5, 0.25, 1.0]).remove(0);
+        let strengths: Vec<_> = aggregated.conclusions.iter().map(|c| c.strength).collect();
+        assert_eq!(strengths, vec![0.75, 0.5]);
+        // `b` is not tested by the second rule
+        assert_eq!(
+            mamdani.aggregate(&[0.5, 1.0, 1.0])[0].conclusions[1].strength,
+            0.5
+        );
+
+        let error = |rule| system(vec![rule]).err().unwrap().to_string();
+        let unknown = error(Rule::new(&[("d", "low")], ("out", "low")));
+        assert!(
+            unknown.contains("tests d, which is not an input variable"),
+            "{unknown}"
+        );
+        let unknown = error(Rule::new(&[("a", "medium")], ("out", "low")));
+        assert!(unknown.contains("a has no term medium"), "{unknown}");
+        let unknown = error(Rule::new(&[("a", "low")], ("out", "medium")));
+        assert!(unknown.contains("out has no term medium"), "{unknown}");
+        let unknown = error(Rule::new(&[("a", "low")], ("b", "low")));
+        assert!(unknown.contains("concludes on b"), "{unknown}");
+        assert!(system(vec![Rule::new(&[], ("out", "low"))]).is_err());
+    }
+
+    #[test]
+    fn test_condition_tree() {
+        let low = FuzzySet::from_points("low", vec![(0.0, 1.0), (1.0, 0.0)]);
+        let high = FuzzySet::from_points("high", vec![(0.0, 0.0), (1.0, 1.0)]);
+        let variable = |name| FuzzyVariable::new(name, vec![low.clone(), high.clone()]);
+        let system = |block: fn(Vec<Rule>) -> RuleBlock| {
+            // history IS high AND (reputation IS high OR NOT endorsement IS very low)
+            let condition = RuleExpr::And(vec![
+                RuleExpr::is("history", "high"),
+                RuleExpr::Or(vec![
+                    RuleExpr::is("reputation", "high"),
+                    RuleExpr::Not(Box::new(RuleExpr::Hedged(
+                        Rc::new(hedges::Very),
+                        Box::new(RuleExpr::is("endorsement", "low")),
+                    ))),
+                ]),
+            ]);
+            let rules = vec![Rule::with_condition(condition, ("trust", "high"))];
+            let inputs = Vec::from(["history", "reputation", "endorsement"].map(variable));
+            Mamdani::with_rule_blocks(inputs, vec![variable("trust")], vec![block(rules)]).unwrap()
+        };
+        let strength =
+            |mamdani: &Mamdani, inputs| mamdani.aggregate(inputs)[0].conclusions[0].strength;
 
-        // Defuzzify the aggregated output
-        let defuzzified_output = (self.output.defuzz_fn)(
-            aggregated_output
+        let mamdani = system(|rules| RuleBlock::new("mamdani", rules));
+        assert_eq!(strength(&mamdani, &[0.75, 0.25, 0.5]), 0.75);
+        assert_eq!(strength(&mamdani, &[0.5, 0.25, 0.0]), 0.25);
+        assert_eq!(strength(&mamdani, &[1.0, 0.0, 0.0]), 0.0);
+
+        let algebraic = system(|rules| {
+            RuleBlock::new("algebraic", rules)
+                .with_conjunction(Rc::new(AlgebraicProduct))
+                .with_disjunction(Rc::new(BoundedSum))
+        });
+        assert_eq!(strength(&algebraic, &[0.5, 0.5, 0.5]), 0.5);
+        assert_eq!(strength(&algebraic, &[0.5, 0.25, 0.0]), 0.125);
+
+        let empty = Rule::with_condition(RuleExpr::Or(vec![]), ("trust", "high"));
+        let inputs = vec![variable("history")];
+        assert!(Mamdani::new(inputs, vec![variable("trust")], vec![empty]).is_err());
+    }
+
+    #[test]
+    fn test_rule_weights() {
+        let weighted = system(|mut rules| {
+            rules[1] = Rule::new(&[("in", "high")], ("out", "high")).with_weight(0.5);
+            RuleBlock::new("weighted", rules)
+        });
+        let strengths = |mamdani: &Mamdani, input| -> Vec<_> {
+            let conclusions = mamdani.aggregate(&[input]).remove(0).conclusions;
+            conclusions
                 .iter()
-                .map(|(_, membership)| membership)
-                .sum::<f64>(),
+                .map(|conclusion| conclusion.strength)
+                .collect()
+        };
+        assert_eq!(strengths(&weighted, 0.5), vec![0.5, 0.25]);
+        assert!(weighted.infer(&[0.5]) < 0.5);
+
+        let threshold = Activation::Threshold(Comparison::GreaterOrEqual, 0.3);
+        let threshold =
+            system(|rules| RuleBlock::new("threshold", rules).with_activation(threshold));
+        assert_eq!(strengths(&threshold, 0.25), vec![0.75]);
+        assert_eq!(strengths(&threshold, 0.5), vec![0.5, 0.5]);
+
+        let error = |rule: Rule, threshold| {
+            let activation = Activation::Threshold(Comparison::GreaterOrEqual, threshold);
+            let block = RuleBlock::new("No1", vec![rule]).with_activation(activation);
+            let low = FuzzySet::from_points("low", vec![(0.0, 1.0), (1.0, 0.0)]);
+            let variable = |name| FuzzyVariable::new(name, vec![low.clone()]);
+            Mamdani::with_rule_blocks(vec![variable("in")], vec![variable("out")], vec![block])
+                .err()
+                .unwrap()
+                .to_string()
+        };
+        let rule = || Rule::new(&[("in", "low")], ("out", "low"));
+        let invalid = error(rule().with_weight(1.5), 0.0);
+        assert!(
+            invalid.contains("rule 1 of No1 has a weight of 1.5"),
+            "{invalid}"
+        );
+        let invalid = error(rule(), -0.5);
+        assert!(
+            invalid.contains("activation Threshold of No1 has a threshold of -0.5"),
+            "{invalid}"
+        );
+    }
+
+    #[test]
+    fn test_multiple_outputs() {
+        let low = FuzzySet::from_points("low", vec![(0.0, 1.0), (1.0, 0.0)]);
+        let high = FuzzySet::from_points("high", vec![(0.0, 0.0), (1.0, 1.0)]);
+        let variable = |name| FuzzyVariable::new(name, vec![low.clone(), high.clone()]);
+        let system = |rules| {
+            let outputs = vec![variable("trust"), variable("monitoring")];
+            Mamdani::new(vec![variable("history")], outputs, rules)
+        };
+
+        let mamdani = system(vec![
+            Rule::new(&[("history", "low")], ("trust", "low"))
+                .with_consequent(("monitoring", "high")),
+            Rule::new(&[("history", "high")], ("trust", "high")),
+        ])
+        .unwrap();
+        let aggregated = mamdani.aggregate(&[0.25]);
+        assert_eq!(aggregated.len(), 2);
+        assert_eq!(aggregated[0].conclusions.len(), 2);
+        assert_eq!(aggregated[1].conclusions.len(), 1);
+        assert_eq!(aggregated[1].conclusions[0].strength, 0.75);
+
+        let outputs = mamdani.infer_outputs(&[0.25]);
+        let names: Vec<_> = outputs.iter().map(|(name, _)| name).collect();
+        assert_eq!(names, vec!["trust", "monitoring"]);
+        assert_eq!(outputs.get("trust"), Some(mamdani.infer(&[0.25])));
+        assert!(outputs["trust"] < 0.5);
+        // Only the high monitoring concludes
+        assert!(outputs["monitoring"] > 0.5);
+        assert!(outputs.get("history").is_none());
+
+        let error = |rules| system(rules).err().unwrap().to_string();
+        let invalid =
+            error(vec![Rule::new(&[("history", "low")], ("trust", "low"))
+                .with_consequent(("history", "high"))]);
+        assert!(
+            invalid.contains("concludes on history, which is not an output"),
+            "{invalid}"
         );
+        let invalid = error(vec![Rule::new(&[("history", "low")], ("trust", "low"))
+            .with_consequent(("monitoring", "medium"))]);
+        assert!(
+            invalid.contains("monitoring has no term medium"),
+            "{invalid}"
+        );
+        let rules = vec![Rule::new(&[("history", "low")], ("trust", "low"))];
+        assert!(Mamdani::new(vec![variable("history")], vec![], rules).is_err());
+        let rules = vec![Rule::new(&[("history", "low")], ("trust", "low"))];
+        let outputs = vec![variable("trust"), variable("trust")];
+        assert!(Mamdani::new(vec![variable("history")], outputs, rules).is_err());
+    }
+
+    #[test]
+    fn test_lock_range() {
+        let mamdani = system(|rules| RuleBlock::new("mamdani", rules));
+        assert_eq!(mamdani.aggregate(&[2.0])[0].inputs, vec![2.0]);
+
+        let mut locked = system(|rules| RuleBlock::new("mamdani", rules));
+        locked.inputs = locked
+            .inputs
+            .into_iter()
+            .map(|input| input.with_lock_range(true))
+            .collect();
+        assert_eq!(locked.aggregate(&[2.0])[0].inputs, vec![1.0]);
+        assert_eq!(locked.infer(&[2.0]), locked.infer(&[1.0]));
+    }
+
+    #[test]
+    fn test_defaults() {
+        let only_high = |output: fn(FuzzyVariable) -> FuzzyVariable| {
+            let low = FuzzySet::from_points("low", vec![(0.0, 1.0), (1.0, 0.0)]);
+            let high = FuzzySet::from_points("high", vec![(0.0, 0.0), (1.0, 1.0)]);
+            let variable = |name| FuzzyVariable::new(name, vec![low.clone(), high.clone()]);
+            let rules = vec![Rule::new(&[("in", "high")], ("out", "high"))];
+            Mamdani::new(vec![variable("in")], vec![output(variable("out"))], rules).unwrap()
+        };
+        let mamdani = only_high(|output| output);
+        let outputs = mamdani.infer_outputs(&[0.0]);
+        assert!(outputs["out"].is_nan());
+        assert!(outputs.is_default("out"));
+        assert!(!mamdani.infer_outputs(&[0.5]).is_default("out"));
+
+        let defaulted = only_high(|output| output.with_default(0.25));
+        assert_eq!(defaulted.infer(&[0.0]), 0.25);
+        let locked = only_high(|output| output.with_range(0.5, 1.0).with_lock_range(true));
+        assert!(locked.infer(&[0.0]).is_nan());
+        let locked = only_high(|output| {
+            output
+                .with_range(0.5, 1.0)
+                .with_lock_range(true)
+                .with_default(0.0)
+        });
+        assert_eq!(locked.infer(&[0.0]), 0.5);
+    }
+
+    #[test]
+    fn test_activation_methods() {
+        let strengths = |activation| -> Vec<_> {
+            let mamdani =
+                system(|rules| RuleBlock::new("block", rules).with_activation(activation));
+            let conclusions = mamdani.aggregate(&[0.25]).remove(0).conclusions;
+            conclusions
+                .iter()
+                .map(|conclusion| conclusion.strength)
+                .collect()
+        };
+        assert_eq!(strengths(Activation::General), vec![0.75, 0.25]);
+        assert_eq!(strengths(Activation::Highest(1)), vec![0.75]);
+        assert_eq!(strengths(Activation::Lowest(1)), vec![0.25]);
+        let last = Activation::Last {
+            count: 2,
+            threshold: 0.5,
+        };
+        assert_eq!(strengths(last), vec![0.75]);
+        assert_eq!(strengths(Activation::Proportional), vec![0.75, 0.25]);
+        let threshold = Activation::Threshold(Comparison::NotEqual, 0.75);
+        assert_eq!(strengths(threshold), vec![0.25]);
+    }
 
-        defuzzified_output
+    #[test]
+    fn test_rule_block_norms() {
+        let bounded =
+            system(|rules| RuleBlock::new("bounded", rules).with_accumulation(Rc::new(BoundedSum)));
+        let aggregated = bounded.aggregate(&[0.5]).remove(0);
+        assert_eq!(aggregated.membership(0.5), 1.0);
+        assert_eq!(aggregated.membership(0.0), 0.5);
     }
 }
@@ -1,3 +1,212 @@
-struct SugenoInference {
+use super::{
+    aggregate, check_weighted, defuzzify, fuzzify, validate, AggregatedSet, FuzzySystem,
+    InferenceError, Outputs,
+};
+use crate::fuzzy_logic::*;
 
+/// Takagi-Sugeno-Kang system, whose output terms are constants, of zero order, or linear
+/// functions of the inputs, of first order, combined by a weighted defuzzifier
+pub struct Sugeno {
+    inputs: Vec<FuzzyVariable>,
+    outputs: Vec<FuzzyVariable>,
+    rule_blocks: Vec<RuleBlock>,
+}
+
+impl Sugeno {
+    /// System with a single rule block of the default norms, see [`RuleBlock::new`]
+    pub fn new(
+        inputs: Vec<FuzzyVariable>,
+        outputs: Vec<FuzzyVariable>,
+        rules: Vec<Rule>,
+    ) -> Result<Self, InferenceError> {
+        Self::with_rule_blocks(inputs, outputs, vec![RuleBlock::new("No1", rules)])
+    }
+
+    /// Fails as [`Mamdani::with_rule_blocks`] does, when an output term is neither constant nor
+    /// linear with a coefficient per input, or when an output is not defuzzified by
+    /// `WeightedAverage` or `WeightedSum`
+    ///
+    /// [`Mamdani::with_rule_blocks`]: super::Mamdani::with_rule_blocks
+    pub fn with_rule_blocks(
+        inputs: Vec<FuzzyVariable>,
+        outputs: Vec<FuzzyVariable>,
+        rule_blocks: Vec<RuleBlock>,
+    ) -> Result<Self, InferenceError> {
+        validate(&inputs, &outputs, &rule_blocks)?;
+        for output in &outputs {
+            let defuzzifier = output.defuzzifier.name();
+            if !matches!(defuzzifier, "WeightedAverage" | "WeightedSum") {
+                return Err(InferenceError::WrongDefuzzifier {
+                    output: output.name.clone(),
+                    defuzzifier: defuzzifier.to_string(),
+                });
+            }
+            check_weighted(&inputs, output)?;
+        }
+        Ok(Self {
+            inputs,
+            outputs,
+            rule_blocks,
+        })
+    }
+
+    /// Rules of every rule block
+    pub fn rules(&self) -> impl Iterator<Item = &Rule> {
+        self.rule_blocks.iter().flat_map(|block| &block.rules)
+    }
+
+    /// Output variable named `name`
+    pub fn output(&self, name: &str) -> Option<&FuzzyVariable> {
+        self.outputs.iter().find(|output| output.name == name)
+    }
+
+    /// Conclusions on each output variable, in their order, of every rule for `input_values`
+    pub fn aggregate(&self, input_values: &[f64]) -> Vec<AggregatedSet> {
+        aggregate(&self.inputs, &self.outputs, &self.rule_blocks, input_values)
+    }
+}
+
+impl FuzzySystem for Sugeno {
+    fn fuzzify(&self, input_values: &[f64]) -> Vec<Vec<f64>> {
+        fuzzify(&self.inputs, input_values)
+    }
+
+    /// Weighted average or sum of the values of the conclusions on each output
+    fn defuzzify_outputs(&self, aggregated: Vec<AggregatedSet>) -> Outputs {
+        defuzzify(&self.outputs, aggregated, |output, aggregated| {
+            output.defuzzifier.defuzzify(aggregated)
+        })
+    }
+
+    fn inputs(&self) -> &[FuzzyVariable] {
+        &self.inputs
+    }
+
+    fn outputs(&self) -> &[FuzzyVariable] {
+        &self.outputs
+    }
+
+    fn rule_blocks(&self) -> &[RuleBlock] {
+        &self.rule_blocks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::fuzzy_logic::defuzzifier::{Centroid, WeightedAverage, WeightedSum};
+
+    fn input(name: &str) -> FuzzyVariable {
+        let low = FuzzySet::from_points("low", vec![(0.0, 1.0), (1.0, 0.0)]);
+        let high = FuzzySet::from_points("high", vec![(0.0, 0.0), (1.0, 1.0)]);
+        FuzzyVariable::new(name, vec![low, high])
+    }
+
+    fn output(terms: Vec<(&str, MembershipFunction)>) -> FuzzyVariable {
+        let sets = terms
+            .into_iter()
+            .map(|(name, function)| FuzzySet::with_function(name, function))
+            .collect();
+        FuzzyVariable::new("out", sets).with_defuzzifier(Rc::new(WeightedAverage))
+    }
+
+    fn rules() -> Vec<Rule> {
+        vec![
+            Rule::new(&[("x", "low")], ("out", "low")),
+            Rule::new(&[("x", "high")], ("out", "high")),
+        ]
+    }
+
+    #[test]
+    fn test_zero_order() {
+        let out = output(vec![
+            ("low", MembershipFunction::constant(10.0).unwrap()),
+            ("high", MembershipFunction::constant(20.0).unwrap()),
+        ]);
+        let sugeno = Sugeno::new(vec![input("x")], vec![out], rules()).unwrap();
+        assert_eq!(sugeno.infer(&[0.0]), 10.0);
+        assert_eq!(sugeno.infer(&[0.25]), 12.5);
+        assert_eq!(sugeno.infer(&[1.0]), 20.0);
+    }
+
+    #[test]
+    fn test_first_order() {
+        // out = 2 x + y when x is low, x - y + 1 when x is high
+        let out = output(vec![
+            ("low", MembershipFunction::linear(&[2.0, 1.0], 0.0).unwrap()),
+            (
+                "high",
+                MembershipFunction::linear(&[1.0, -1.0], 1.0).unwrap(),
+            ),
+        ]);
+        let sugeno = Sugeno::new(vec![input("x"), input("y")], vec![out], rules()).unwrap();
+        assert_eq!(sugeno.infer(&[0.0, 0.5]), 0.5);
+        assert_eq!(sugeno.infer(&[1.0, 0.5]), 1.5);
+        assert_eq!(sugeno.infer(&[0.5, 0.5]), 1.25);
+    }
+
+    #[test]
+    fn test_weighted_sum() {
+        let out = output(vec![
+            ("low", MembershipFunction::constant(10.0).unwrap()),
+            ("high", MembershipFunction::constant(20.0).unwrap()),
+        ])
+        .with_defuzzifier(Rc::new(WeightedSum));
+        let rules = vec![
+            Rule::new(&[("x", "low")], ("out", "low")).with_weight(0.5),
+            Rule::new(&[("x", "high")], ("out", "high")),
+        ];
+        let sugeno = Sugeno::new(vec![input("x")], vec![out], rules).unwrap();
+        assert_eq!(sugeno.infer(&[0.5]), 12.5);
+        assert_eq!(sugeno.infer_outputs(&[0.5])["out"], 12.5);
+    }
+
+    #[test]
+    fn test_validation() {
+        let constant = || MembershipFunction::constant(1.0).unwrap();
+        let error = |out: FuzzyVariable| {
+            Sugeno::new(vec![input("x")], vec![out], rules())
+                .err()
+                .unwrap()
+        };
+        let linear = MembershipFunction::linear(&[1.0, 1.0], 0.0).unwrap();
+        assert_eq!(
+            error(output(vec![("low", constant()), ("high", linear)])),
+            InferenceError::CoefficientCount {
+                output: "out".to_string(),
+                term: "high".to_string(),
+                coefficients: 2,
+                inputs: 1
+            }
+        );
+        let ramp = MembershipFunction::ramp(0.0, 1.0).unwrap();
+        let error_ramp = error(output(vec![("low", constant()), ("high", ramp)]));
+        assert_eq!(
+            error_ramp.to_string(),
+            "term high of out is Ramp, weighted outputs need Constant or Linear terms"
+        );
+        assert_eq!(
+            error_ramp,
+            InferenceError::NotConstantOrLinear {
+                output: "out".to_string(),
+                term: "high".to_string(),
+                function: "Ramp".to_string()
+            }
+        );
+        let centroid = output(vec![("low", constant()), ("high", constant())])
+            .with_defuzzifier(Rc::new(Centroid::default()));
+        assert_eq!(
+            error(centroid),
+            InferenceError::WrongDefuzzifier {
+                output: "out".to_string(),
+                defuzzifier: "Centroid".to_string()
+            }
+        );
+        assert!(matches!(
+            error(output(vec![("low", constant())])),
+            InferenceError::UnresolvedTerm { rule: 2, .. }
+        ));
+    }
 }
@@ -0,0 +1,360 @@
+//! Trace of an inference, every step from the memberships of the inputs to the crisp values of
+//! the outputs, see [`FuzzySystem::infer_traced`].
+//!
+//! The trace serializes with serde, sets being sampled over the universe of their variable.
+//!
+//! [`FuzzySystem::infer_traced`]: super::FuzzySystem::infer_traced
+use serde::Serialize;
+
+use super::{fuzzify, truth, Activated, AggregatedSet, Outputs};
+use crate::fuzzy_logic::{FuzzyVariable, Proposition, RuleBlock, RuleExpr};
+
+/// Steps of an inference, in the order of the variables and rules of the system
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Trace {
+    pub inputs: Vec<InputTrace>,
+    pub rules: Vec<RuleTrace>,
+    pub outputs: Vec<OutputTrace>,
+}
+
+/// Crisp value of an input, clamped when its range is locked, and its membership to each term
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InputTrace {
+    pub variable: String,
+    pub value: f64,
+    pub memberships: Vec<Membership>,
+}
+
+/// Membership of a crisp value to a term
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Membership {
+    pub term: String,
+    pub degree: f64,
+}
+
+/// Rule with the memberships its condition tested and the degree it concluded at
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RuleTrace {
+    pub block: String,
+    /// Position of the rule in its block, from 1
+    pub number: usize,
+    pub condition: String,
+    /// Propositions tested by the condition with the hedges and negations applied to them
+    pub antecedents: Vec<Antecedent>,
+    pub weight: f64,
+    /// Truth of the condition scaled by the weight
+    pub strength: f64,
+    /// Degree the rule concludes at, `None` when the activation of its block skips it
+    pub activation: Option<f64>,
+    pub consequents: Vec<Proposition>,
+}
+
+/// `variable IS [modifiers] term` tested by a condition, the membership of the input to the term
+/// and the truth of the proposition once modified
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Antecedent {
+    pub variable: String,
+    /// `not` and the names of the hedges applied to the proposition, outermost first
+    pub modifiers: Vec<String>,
+    pub term: String,
+    pub membership: f64,
+    /// Truth of the modified proposition, the membership when it has no modifier
+    pub degree: f64,
+}
+
+/// Conclusions of the rules on an output, their aggregation and its crisp value
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OutputTrace {
+    pub variable: String,
+    /// Values the sets are sampled at, empty when the output has no range
+    pub universe: Vec<f64>,
+    pub conclusions: Vec<ConclusionTrace>,
+    /// Aggregated set sampled over the universe
+    pub aggregated: Vec<f64>,
+    pub value: f64,
+    /// Whether no rule fired on the output, which took its default value
+    pub default: bool,
+}
+
+/// Term of an output concluded by an activated rule, implied at its activation degree
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConclusionTrace {
+    pub block: String,
+    pub rule: usize,
+    pub term: String,
+    pub degree: f64,
+    /// Implied set sampled over the universe of the output
+    pub implied: Vec<f64>,
+}
+
+impl Trace {
+    /// Infers `outputs` from `input_values` by the rules of `rule_blocks`, tracing every step.
+    /// The aggregated sets of the trace are the ones `defuzzify` turns into the crisp values
+    pub fn new(
+        inputs: &[FuzzyVariable],
+        outputs: &[FuzzyVariable],
+        rule_blocks: &[RuleBlock],
+        input_values: &[f64],
+        defuzzify: impl FnOnce(Vec<AggregatedSet>) -> Outputs,
+    ) -> (Outputs, Self) {
+        let fuzzy_inputs = fuzzify(inputs, input_values);
+        let clamped: Vec<_> = inputs
+            .iter()
+            .zip(input_values)
+            .map(|(input, &value)| input.clamp(value))
+            .collect();
+        let input_traces = inputs
+            .iter()
+            .zip(&clamped)
+            .zip(&fuzzy_inputs)
+            .map(|((input, &value), memberships)| InputTrace {
+                variable: input.name.clone(),
+                value,
+                memberships: input
+                    .fuzzy_sets
+                    .iter()
+                    .zip(memberships)
+                    .map(|(set, &degree)| Membership {
+                        term: set.name.clone(),
+                        degree,
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        let universes: Vec<_> = outputs
+            .iter()
+            .map(|output| output.universe().unwrap_or_default())
+            .collect();
+        let mut conclusions: Vec<Vec<ConclusionTrace>> = vec![Vec::new(); outputs.len()];
+        let mut aggregated: Vec<_> = outputs
+            .iter()
+            .map(|output| AggregatedSet {
+                range: output.range(),
+                inputs: clamped.clone(),
+                conclusions: Vec::new(),
+            })
+            .collect();
+        let mut rules = Vec::new();
+        for block in rule_blocks {
+            let truth = |proposition: &Proposition| truth(inputs, &fuzzy_inputs, proposition);
+            let strengths: Vec<_> = block
+                .rules
+                .iter()
+                .map(|rule| rule.activation(block, &truth))
+                .collect();
+            let activated = block.activation.activate(&strengths);
+            for (index, (rule, activation)) in block.rules.iter().zip(activated).enumerate() {
+                let number = index + 1;
+                rules.push(RuleTrace {
+                    block: block.name.clone(),
+                    number,
+                    condition: rule.condition.to_string(),
+                    antecedents: antecedents(&rule.condition)
+                        .into_iter()
+                        .map(|(modifiers, expression, proposition)| Antecedent {
+                            variable: proposition.variable.clone(),
+                            modifiers,
+                            term: proposition.term.clone(),
+                            membership: truth(proposition),
+                            degree: expression.evaluate(block, &truth),
+                        })
+                        .collect(),
+                    weight: rule.weight,
+                    strength: strengths[index],
+                    activation,
+                    consequents: rule.consequents.clone(),
+                });
+                let Some(degree) = activation else {
+                    continue;
+                };
+                for consequent in &rule.consequents {
+                    let Some(output) = outputs
+                        .iter()
+                        .position(|output| output.name == consequent.variable)
+                    else {
+                        continue;
+                    };
+                    let Some(set) = outputs[output].set(&consequent.term) else {
+                        continue;
+                    };
+                    conclusions[output].push(ConclusionTrace {
+                        block: block.name.clone(),
+                        rule: number,
+                        term: set.name.clone(),
+                        degree,
+                        implied: universes[output]
+                            .iter()
+                            .map(|&x| block.implication.compute(degree, set.membership(x)))
+                            .collect(),
+                    });
+                    aggregated[output].conclusions.push(Activated {
+                        set: set.clone(),
+                        strength: degree,
+                        implication: block.implication.clone(),
+                        accumulation: block.accumulation.clone(),
+                    });
+                }
+            }
+        }
+
+        let sampled: Vec<Vec<f64>> = aggregated
+            .iter()
+            .zip(&universes)
+            .map(|(set, universe)| universe.iter().map(|&x| set.membership(x)).collect())
+            .collect();
+        let crisp = defuzzify(aggregated);
+        let output_traces = outputs
+            .iter()
+            .zip(universes)
+            .zip(conclusions)
+            .zip(sampled)
+            .map(
+                |(((output, universe), conclusions), aggregated)| OutputTrace {
+                    variable: output.name.clone(),
+                    universe,
+                    conclusions,
+                    aggregated,
+                    value: crisp.get(&output.name).unwrap_or(f64::NAN),
+                    default: crisp.is_default(&output.name),
+                },
+            )
+            .collect();
+
+        let trace = Self {
+            inputs: input_traces,
+            rules,
+            outputs: output_traces,
+        };
+        (crisp, trace)
+    }
+
+    /// Rules that concluded at a positive degree
+    pub fn fired(&self) -> impl Iterator<Item = &RuleTrace> {
+        self.rules
+            .iter()
+            .filter(|rule| rule.activation.is_some_and(|degree| degree > 0.0))
+    }
+
+    /// Trace of the output variable named `name`
+    pub fn output(&self, name: &str) -> Option<&OutputTrace> {
+        self.outputs.iter().find(|output| output.variable == name)
+    }
+}
+
+/// Propositions of `expression` from left to right, each with the negations and hedges applied
+/// directly to it and the expression applying them.
+/// The modifiers of a compound expression, `NOT (a AND b)`, are only shown by the condition
+fn antecedents(expression: &RuleExpr) -> Vec<(Vec<String>, &RuleExpr, &Proposition)> {
+    let mut modifiers = Vec::new();
+    let mut operand = expression;
+    loop {
+        match operand {
+            RuleExpr::Is(proposition) => return vec![(modifiers, expression, proposition)],
+            RuleExpr::Not(inner) => {
+                modifiers.push("not".to_string());
+                operand = inner;
+            }
+            RuleExpr::Hedged(hedge, inner) => {
+                modifiers.push(hedge.name().to_string());
+                operand = inner;
+            }
+            RuleExpr::And(operands) | RuleExpr::Or(operands) => {
+                return operands.iter().flat_map(antecedents).collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::fuzzy_logic::inference::{FuzzySystem, Mamdani};
+    use crate::fuzzy_logic::{hedges, FuzzySet, Rule};
+
+    fn variable(name: &str, max: f64) -> FuzzyVariable {
+        let low = FuzzySet::from_points("low", vec![(0.0, 1.0), (max, 0.0)]);
+        let high = FuzzySet::from_points("high", vec![(0.0, 0.0), (max, 1.0)]);
+        FuzzyVariable::new(name, vec![low, high])
+    }
+
+    fn mamdani() -> Mamdani {
+        let out = variable("out", 10.0).with_resolution(10);
+        let rules = vec![
+            Rule::new(&[("x", "low"), ("y", "high")], ("out", "low")),
+            Rule::new(&[("x", "high")], ("out", "high")).with_weight(0.5),
+        ];
+        Mamdani::new(
+            vec![variable("x", 1.0), variable("y", 1.0)],
+            vec![out],
+            rules,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_trace() {
+        let mamdani = mamdani();
+        let (outputs, trace) = mamdani.infer_traced(&[0.25, 1.0]);
+        assert_eq!(outputs, mamdani.infer_outputs(&[0.25, 1.0]));
+
+        let x = &trace.inputs[0];
+        assert_eq!((x.variable.as_str(), x.value), ("x", 0.25));
+        let memberships: Vec<_> = x.memberships.iter().map(|m| m.degree).collect();
+        assert_eq!(memberships, vec![0.75, 0.25]);
+
+        let (first, second) = (&trace.rules[0], &trace.rules[1]);
+        assert_eq!(first.condition, "x IS low AND y IS high");
+        let antecedents: Vec<_> = first.antecedents.iter().map(|a| a.membership).collect();
+        assert_eq!(antecedents, vec![0.75, 1.0]);
+        assert_eq!((first.strength, first.activation), (0.75, Some(0.75)));
+        assert_eq!((second.number, second.weight), (2, 0.5));
+        assert_eq!(second.strength, 0.125);
+        assert_eq!(trace.fired().count(), 2);
+
+        let out = trace.output("out").unwrap();
+        assert_eq!(out.universe.len(), 11);
+        assert_eq!(out.conclusions[0].implied[0], 0.75);
+        assert_eq!(out.conclusions[1].implied[10], 0.125);
+        assert_eq!((out.aggregated[0], out.aggregated[10]), (0.75, 0.125));
+        assert_eq!(out.value, outputs["out"]);
+        assert!(!out.default);
+    }
+
+    #[test]
+    fn test_trace_modifiers() {
+        let very = RuleExpr::Hedged(Rc::new(hedges::Very), Box::new(RuleExpr::is("x", "high")));
+        let condition = RuleExpr::And(vec![
+            RuleExpr::Not(Box::new(very)),
+            RuleExpr::Not(Box::new(RuleExpr::is("y", "low"))),
+        ]);
+        let rules = vec![Rule::with_condition(condition, ("out", "high"))];
+        let inputs = vec![variable("x", 1.0), variable("y", 1.0)];
+        let mamdani = Mamdani::new(inputs, vec![variable("out", 10.0)], rules).unwrap();
+        let (_, trace) = mamdani.infer_traced(&[0.5, 0.25]);
+
+        let rule = &trace.rules[0];
+        assert_eq!(rule.condition, "NOT (x IS very high) AND y IS NOT low");
+        let (x, y) = (&rule.antecedents[0], &rule.antecedents[1]);
+        assert_eq!(x.modifiers, vec!["not", "very"]);
+        assert_eq!((x.membership, x.degree), (0.5, 0.75));
+        assert_eq!(y.modifiers, vec!["not"]);
+        assert_eq!((y.membership, y.degree), (0.75, 0.25));
+        assert_eq!(rule.strength, 0.25);
+        let json = serde_json::to_value(&trace).unwrap();
+        assert_eq!(json["rules"][0]["antecedents"][0]["degree"], 0.75);
+    }
+
+    #[test]
+    fn test_trace_json() {
+        let (_, trace) = mamdani().infer_traced(&[0.0, 0.0]);
+        let json = serde_json::to_value(&trace).unwrap();
+        assert_eq!(json["rules"][1]["condition"], "x IS high");
+        assert_eq!(json["rules"][1]["consequents"][0]["term"], "high");
+        assert_eq!(json["outputs"][0]["default"], true);
+        // The NaN value of an output no rule fired on serializes as null
+        assert!(json["outputs"][0]["value"].is_null());
+    }
+}
@@ -0,0 +1,208 @@
+use super::{
+    aggregate, defuzzify, fuzzify, validate, AggregatedSet, FuzzySystem, InferenceError, Outputs,
+};
+use crate::fuzzy_logic::*;
+
+/// Tsukamoto system, whose output terms are monotonic. Each rule concludes on the value its
+/// firing strength is the membership of, and the output is the average of those values weighted
+/// by the strengths
+pub struct Tsukamoto {
+    inputs: Vec<FuzzyVariable>,
+    outputs: Vec<FuzzyVariable>,
+    rule_blocks: Vec<RuleBlock>,
+}
+
+impl Tsukamoto {
+    /// System with a single rule block of the default norms, see [`RuleBlock::new`]
+    pub fn new(
+        inputs: Vec<FuzzyVariable>,
+        outputs: Vec<FuzzyVariable>,
+        rules: Vec<Rule>,
+    ) -> Result<Self, InferenceError> {
+        Self::with_rule_blocks(inputs, outputs, vec![RuleBlock::new("No1", rules)])
+    }
+
+    /// Fails as [`Mamdani::with_rule_blocks`] does, when an output has no range or when an
+    /// output term is not monotonic, see [`MembershipFunction::is_monotonic`].
+    /// The defuzzifiers of the outputs are not used
+    ///
+    /// [`Mamdani::with_rule_blocks`]: super::Mamdani::with_rule_blocks
+    pub fn with_rule_blocks(
+        inputs: Vec<FuzzyVariable>,
+        outputs: Vec<FuzzyVariable>,
+        rule_blocks: Vec<RuleBlock>,
+    ) -> Result<Self, InferenceError> {
+        validate(&inputs, &outputs, &rule_blocks)?;
+        for output in &outputs {
+            if output.range().is_none() {
+                return Err(InferenceError::NoRange {
+                    output: output.name.clone(),
+                });
+            }
+            if let Some(set) = output
+                .fuzzy_sets
+                .iter()
+                .find(|set| !set.function.is_monotonic())
+            {
+                return Err(InferenceError::NotMonotonic {
+                    output: output.name.clone(),
+                    term: set.name.clone(),
+                    function: set.function.name().to_string(),
+                });
+            }
+        }
+        Ok(Self {
+            inputs,
+            outputs,
+            rule_blocks,
+        })
+    }
+
+    /// Rules of every rule block
+    pub fn rules(&self) -> impl Iterator<Item = &Rule> {
+        self.rule_blocks.iter().flat_map(|block| &block.rules)
+    }
+
+    /// Output variable named `name`
+    pub fn output(&self, name: &str) -> Option<&FuzzyVariable> {
+        self.outputs.iter().find(|output| output.name == name)
+    }
+
+    /// Conclusions on each output variable, in their order, of every rule for `input_values`
+    pub fn aggregate(&self, input_values: &[f64]) -> Vec<AggregatedSet> {
+        aggregate(&self.inputs, &self.outputs, &self.rule_blocks, input_values)
+    }
+}
+
+impl FuzzySystem for Tsukamoto {
+    fn fuzzify(&self, input_values: &[f64]) -> Vec<Vec<f64>> {
+        fuzzify(&self.inputs, input_values)
+    }
+
+    /// Weighted average of the values the conclusions on each output invert to
+    fn defuzzify_outputs(&self, aggregated: Vec<AggregatedSet>) -> Outputs {
+        defuzzify(&self.outputs, aggregated, |_, aggregated| {
+            invert(aggregated)
+        })
+    }
+
+    fn inputs(&self) -> &[FuzzyVariable] {
+        &self.inputs
+    }
+
+    fn outputs(&self) -> &[FuzzyVariable] {
+        &self.outputs
+    }
+
+    fn rule_blocks(&self) -> &[RuleBlock] {
+        &self.rule_blocks
+    }
+}
+
+/// Average of the values whose membership to each conclusion is its strength, weighted by it.
+/// Values beyond the range of the output, as sigmoids give for strengths close to 0 or 1, are
+/// held at its bounds, and conclusions that invert to no finite value are left out
+fn invert(aggregated: &AggregatedSet) -> f64 {
+    // Tsukamoto outputs have a range
+    let Some((min, max)) = aggregated.range else {
+        return f64::NAN;
+    };
+    let (weights, sum) = aggregated
+        .conclusions
+        .iter()
+        .filter(|conclusion| conclusion.strength > 0.0)
+        .filter_map(|conclusion| {
+            let value = conclusion.set.inverse(conclusion.strength)?;
+            let value = value.clamp(min, max);
+            value.is_finite().then_some((conclusion.strength, value))
+        })
+        .fold((0.0, 0.0), |(weights, sum), (strength, value)| {
+            (weights + strength, sum + strength * value)
+        });
+    sum / weights
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input() -> FuzzyVariable {
+        let low = FuzzySet::from_points("low", vec![(0.0, 1.0), (1.0, 0.0)]);
+        let high = FuzzySet::from_points("high", vec![(0.0, 0.0), (1.0, 1.0)]);
+        FuzzyVariable::new("x", vec![low, high])
+    }
+
+    fn output(low: MembershipFunction, high: MembershipFunction) -> FuzzyVariable {
+        let sets = vec![
+            FuzzySet::with_function("low", low),
+            FuzzySet::with_function("high", high),
+        ];
+        FuzzyVariable::new("out", sets).with_range(0.0, 10.0)
+    }
+
+    fn rules() -> Vec<Rule> {
+        vec![
+            Rule::new(&[("x", "low")], ("out", "low")),
+            Rule::new(&[("x", "high")], ("out", "high")),
+        ]
+    }
+
+    #[test]
+    fn test_ramps() {
+        let out = output(
+            MembershipFunction::ramp(10.0, 0.0).unwrap(),
+            MembershipFunction::ramp(0.0, 10.0).unwrap(),
+        );
+        let tsukamoto = Tsukamoto::new(vec![input()], vec![out], rules()).unwrap();
+        // low at 0.75 inverts to 2.5 and high at 0.25 to 2.5
+        assert_eq!(tsukamoto.infer(&[0.25]), 2.5);
+        assert_eq!(tsukamoto.infer(&[0.0]), 0.0);
+        assert_eq!(tsukamoto.infer(&[1.0]), 10.0);
+        let scores: Vec<_> = (0..=10)
+            .map(|x| tsukamoto.infer(&[x as f64 / 10.0]))
+            .collect();
+        assert!(scores.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    #[test]
+    fn test_sigmoids() {
+        let out = output(
+            MembershipFunction::sigmoid(-2.0, 5.0).unwrap(),
+            MembershipFunction::sigmoid(2.0, 5.0).unwrap(),
+        );
+        let tsukamoto = Tsukamoto::new(vec![input()], vec![out], rules()).unwrap();
+        assert!((tsukamoto.infer(&[0.5]) - 5.0).abs() < 1e-12);
+        // A full strength inverts to an infinite value, held at the bound of the range
+        assert_eq!(tsukamoto.infer(&[1.0]), 10.0);
+    }
+
+    #[test]
+    fn test_validation() {
+        let out = output(
+            MembershipFunction::ramp(10.0, 0.0).unwrap(),
+            MembershipFunction::triangular(0.0, 5.0, 10.0).unwrap(),
+        );
+        let error = Tsukamoto::new(vec![input()], vec![out], rules()).err();
+        assert_eq!(
+            error,
+            Some(InferenceError::NotMonotonic {
+                output: "out".to_string(),
+                term: "high".to_string(),
+                function: "Triangular".to_string()
+            })
+        );
+
+        let sigmoids = vec![
+            FuzzySet::with_function("low", MembershipFunction::sigmoid(-2.0, 5.0).unwrap()),
+            FuzzySet::with_function("high", MembershipFunction::sigmoid(2.0, 5.0).unwrap()),
+        ];
+        let unbounded = FuzzyVariable::new("out", sigmoids);
+        let error = Tsukamoto::new(vec![input()], vec![unbounded], rules()).err();
+        assert_eq!(
+            error,
+            Some(InferenceError::NoRange {
+                output: "out".to_string()
+            })
+        );
+    }
+}
@@ -0,0 +1,343 @@
+//! Short justifications of inferred values in natural language, built from the trace of an
+//! inference, see [`FuzzySystem::infer_traced`].
+//!
+//! Each output is justified by its strongest conclusion, for instance
+//! `Trust level is medium (5.2) mainly because reputation is high (0.8) and transaction history
+//! is low (0.6) (rule 5)`. The sentences are filled from templates whose `{placeholders}` take
+//! the names of the variables and terms, relabelled for localization.
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::fuzzy_logic::inference::{FuzzySystem, OutputTrace, RuleTrace, Trace};
+
+/// Writes the justification of each output of an inference from templates
+///
+/// - the decision template takes `{output}`, `{term}` and `{value}`, the crisp value of the
+///   output, `{degree}`, the degree its strongest conclusion is activated at, `{reasons}`,
+///   `{block}` and `{rule}`, the number of the rule concluding it
+/// - the reason template takes, for each proposition tested by the rule, `{variable}`, `{term}`,
+///   preceded by the negation and hedges applied to it, `{degree}`, the truth of the proposition
+///   once modified, and `{value}`, the crisp input
+/// - the default template takes `{output}` and `{value}` for the outputs no rule fired on
+#[derive(Clone)]
+pub struct Justifier {
+    decision: String,
+    reason: String,
+    separator: String,
+    last_separator: String,
+    default: String,
+    precision: usize,
+    labels: HashMap<String, String>,
+    localizer: Rc<dyn Fn(&str) -> String>,
+}
+
+impl Default for Justifier {
+    /// English justifier, names with their underscores replaced by spaces
+    fn default() -> Self {
+        Self {
+            decision: "{output} is {term} ({value}) mainly because {reasons} (rule {rule})"
+                .to_string(),
+            reason: "{variable} is {term} ({degree})".to_string(),
+            separator: ", ".to_string(),
+            last_separator: " and ".to_string(),
+            default: "{output} is {value} by default, no rule fired".to_string(),
+            precision: 2,
+            labels: HashMap::new(),
+            localizer: Rc::new(|name| name.replace('_', " ")),
+        }
+    }
+}
+
+impl Justifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_decision(mut self, template: &str) -> Self {
+        self.decision = template.to_string();
+        self
+    }
+
+    pub fn with_reason(mut self, template: &str) -> Self {
+        self.reason = template.to_string();
+        self
+    }
+
+    /// Sets what joins the reasons, `separator` between them but the last two joined by `last`
+    pub fn with_separators(mut self, separator: &str, last: &str) -> Self {
+        self.separator = separator.to_string();
+        self.last_separator = last.to_string();
+        self
+    }
+
+    pub fn with_default(mut self, template: &str) -> Self {
+        self.default = template.to_string();
+        self
+    }
+
+    /// Sets the number of decimals of the values and degrees
+    pub fn with_precision(mut self, precision: usize) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Writes the variable or term named `name` as `label`
+    pub fn with_label(mut self, name: &str, label: &str) -> Self {
+        self.labels.insert(name.to_string(), label.to_string());
+        self
+    }
+
+    /// Writes the variables and terms without a label as `localizer` names them
+    pub fn with_localizer(mut self, localizer: Rc<dyn Fn(&str) -> String>) -> Self {
+        self.localizer = localizer;
+        self
+    }
+
+    /// Justification of each output of `system` inferred from `input_values`, in their order
+    pub fn justify(&self, system: &impl FuzzySystem, input_values: &[f64]) -> Vec<String> {
+        let (_, trace) = system.infer_traced(input_values);
+        self.justify_trace(&trace)
+    }
+
+    /// Justification of each output of a traced inference, in their order
+    pub fn justify_trace(&self, trace: &Trace) -> Vec<String> {
+        trace
+            .outputs
+            .iter()
+            .map(|output| self.justify_output(trace, output))
+            .collect()
+    }
+
+    fn justify_output(&self, trace: &Trace, output: &OutputTrace) -> String {
+        let value = self.number(output.value);
+        // The first of the strongest conclusions decides
+        let strongest = output
+            .conclusions
+            .iter()
+            .filter(|conclusion| conclusion.degree > 0.0)
+            .reduce(|strongest, conclusion| {
+                if conclusion.degree > strongest.degree {
+                    conclusion
+                } else {
+                    strongest
+                }
+            });
+        let rule = strongest.and_then(|conclusion| {
+            trace
+                .rules
+                .iter()
+                .find(|rule| rule.block == conclusion.block && rule.number == conclusion.rule)
+        });
+        let (Some(conclusion), Some(rule)) = (strongest, rule) else {
+            return capitalize(fill(
+                &self.default,
+                &[("output", self.label(&output.variable)), ("value", value)],
+            ));
+        };
+        capitalize(fill(
+            &self.decision,
+            &[
+                ("output", self.label(&output.variable)),
+                ("term", self.label(&conclusion.term)),
+                ("value", value),
+                ("degree", self.number(conclusion.degree)),
+                ("reasons", self.reasons(trace, rule)),
+                ("block", conclusion.block.clone()),
+                ("rule", conclusion.rule.to_string()),
+            ],
+        ))
+    }
+
+    /// Propositions tested by `rule`, with their negations and hedges, joined by the separators
+    fn reasons(&self, trace: &Trace, rule: &RuleTrace) -> String {
+        let reasons: Vec<_> = rule
+            .antecedents
+            .iter()
+            .map(|antecedent| {
+                let value = trace
+                    .inputs
+                    .iter()
+                    .find(|input| input.variable == antecedent.variable)
+                    .map_or(f64::NAN, |input| input.value);
+                // `not very low`, each word labelled
+                let term = antecedent
+                    .modifiers
+                    .iter()
+                    .chain([&antecedent.term])
+                    .map(|name| self.label(name))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                fill(
+                    &self.reason,
+                    &[
+                        ("variable", self.label(&antecedent.variable)),
+                        ("term", term),
+                        ("degree", self.number(antecedent.degree)),
+                        ("value", self.number(value)),
+                    ],
+                )
+            })
+            .collect();
+        match reasons.split_last() {
+            Some((last, [])) => last.clone(),
+            Some((last, rest)) => rest.join(&self.separator) + &self.last_separator + last,
+            None => String::new(),
+        }
+    }
+
+    fn label(&self, name: &str) -> String {
+        match self.labels.get(name) {
+            Some(label) => label.clone(),
+            None => (self.localizer)(name),
+        }
+    }
+
+    fn number(&self, value: f64) -> String {
+        format!("{value:.*}", self.precision)
+    }
+}
+
+/// `template` with each `{name}` replaced by the value of `name`, unknown placeholders are kept
+fn fill(template: &str, values: &[(&str, String)]) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        filled.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let value = rest.find('}').and_then(|end| {
+            values
+                .iter()
+                .find(|(name, _)| *name == &rest[1..end])
+                .map(|(_, value)| (end, value))
+        });
+        match value {
+            Some((end, value)) => {
+                filled.push_str(value);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                filled.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    filled.push_str(rest);
+    filled
+}
+
+/// Sentence starting with an uppercase letter
+fn capitalize(sentence: String) -> String {
+    let mut chars = sentence.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => sentence,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzzy_logic::inference::Mamdani;
+    use crate::fuzzy_logic::{hedges, FuzzySet, FuzzyVariable, Rule, RuleExpr};
+
+    fn variable(name: &str) -> FuzzyVariable {
+        let low = FuzzySet::from_points("low", vec![(0.0, 1.0), (10.0, 0.0)]);
+        let high = FuzzySet::from_points("high", vec![(0.0, 0.0), (10.0, 1.0)]);
+        FuzzyVariable::new(name, vec![low, high]).with_range(0.0, 10.0)
+    }
+
+    fn mamdani() -> Mamdani {
+        let rules = vec![
+            Rule::new(&[("reputation", "low")], ("trust_level", "low")),
+            Rule::new(
+                &[("reputation", "high"), ("transaction_history", "low")],
+                ("trust_level", "high"),
+            ),
+        ];
+        let inputs = vec![variable("reputation"), variable("transaction_history")];
+        Mamdani::new(inputs, vec![variable("trust_level")], rules).unwrap()
+    }
+
+    #[test]
+    fn test_justify() {
+        let mamdani = mamdani();
+        let value = mamdani.infer(&[8.0, 4.0]);
+        assert_eq!(
+            Justifier::new().justify(&mamdani, &[8.0, 4.0]),
+            vec![format!(
+                "Trust level is high ({value:.2}) mainly because reputation is high (0.80) and \
+                 transaction history is low (0.60) (rule 2)"
+            )]
+        );
+        let justifier = Justifier::new()
+            .with_precision(1)
+            .with_decision("{output}: {term}, {reasons}")
+            .with_reason("{variable} = {value}")
+            .with_default("{output}: {value}");
+        assert_eq!(
+            justifier.justify(&mamdani, &[2.0, 4.0]),
+            vec!["Trust level: low, reputation = 2.0"]
+        );
+        assert_eq!(
+            justifier.justify(&mamdani, &[10.0, 10.0]),
+            vec!["Trust level: NaN"]
+        );
+    }
+
+    #[test]
+    fn test_justify_modifiers() {
+        let very = RuleExpr::Hedged(
+            Rc::new(hedges::Very),
+            Box::new(RuleExpr::is("reputation", "high")),
+        );
+        let not_low = RuleExpr::Not(Box::new(RuleExpr::is("reputation", "low")));
+        let rules = vec![
+            Rule::with_condition(very, ("trust_level", "high")),
+            Rule::with_condition(not_low, ("risk", "low")),
+        ];
+        let outputs = vec![variable("trust_level"), variable("risk")];
+        let mamdani = Mamdani::new(vec![variable("reputation")], outputs, rules).unwrap();
+        let justifier = Justifier::new().with_decision("{output}: {reasons}");
+        assert_eq!(
+            justifier.justify(&mamdani, &[9.0]),
+            vec![
+                "Trust level: reputation is very high (0.81)",
+                "Risk: reputation is not low (0.90)"
+            ]
+        );
+        let justifier = justifier
+            .with_reason("{variable} {term}")
+            .with_label("not", "n'est pas")
+            .with_label("low", "faible");
+        assert_eq!(
+            justifier.justify(&mamdani, &[9.0])[1],
+            "Risk: reputation n'est pas faible"
+        );
+    }
+
+    #[test]
+    fn test_localization() {
+        let justifier = Justifier::new()
+            .with_decision("{output} est {term} ({value}) surtout car {reasons} (règle {rule})")
+            .with_reason("{variable} est {term}")
+            .with_separators(", ", " et ")
+            .with_label("trust_level", "la confiance")
+            .with_label("transaction_history", "l'historique")
+            .with_localizer(Rc::new(|name| match name {
+                "low" => "faible".to_string(),
+                "high" => "élevée".to_string(),
+                name => format!("la {name}"),
+            }));
+        let justification = justifier.justify(&mamdani(), &[8.0, 0.0]);
+        assert!(justification[0].starts_with("La confiance est élevée ("));
+        assert!(justification[0].ends_with(
+            "surtout car la reputation est élevée et l'historique est faible (règle 2)"
+        ));
+    }
+
+    #[test]
+    fn test_fill() {
+        let values = [("a", "1".to_string()), ("b", "{a}".to_string())];
+        assert_eq!(fill("{a} {b} {c} {", &values), "1 {a} {c} {");
+    }
+}
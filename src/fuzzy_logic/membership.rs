@@ -0,0 +1,1028 @@
+use std::f64::consts::PI;
+use std::fmt;
+use std::rc::Rc;
+
+use anyhow::{bail, Result};
+
+/// Membership function of a fuzzy set, keeping the parameters of its shape.
+///
+/// Shapes follow the terms of fuzzylite, whose `Discrete` term is a [`PiecewiseLinear`] function.
+///
+/// [`PiecewiseLinear`]: MembershipFunction::PiecewiseLinear
+#[derive(Clone)]
+pub enum MembershipFunction {
+    /// Rises from `a` to a peak at `b` and falls to `c`
+    Triangular { a: f64, b: f64, c: f64 },
+    /// Rises from `a` to `b`, is 1 up to `c` and falls to `d`
+    Trapezoidal { a: f64, b: f64, c: f64, d: f64 },
+    /// Bell curve centered on `c` with a standard deviation `sigma`
+    Gaussian { c: f64, sigma: f64 },
+    /// Logistic curve of steepness `a` crossing 0.5 at `c`
+    Sigmoid { a: f64, c: f64 },
+    /// Generalized bell of width `a` and slope `b` centered on `c`
+    Bell { a: f64, b: f64, c: f64 },
+    /// Linear interpolation between points sorted by `x`, see [`interpolate`]
+    PiecewiseLinear { points: Rc<[(f64, f64)]> },
+    /// `(x - start) / (end - start)` clamped to `[0, 1]`, rising when `start < end` and falling
+    /// otherwise
+    Ramp { start: f64, end: f64 },
+    /// 1 on `[start, end]` and 0 elsewhere
+    Rectangle { start: f64, end: f64 },
+    /// Smooth rise from 0 at `start` to 1 at `end`, `2((x - start) / (end - start))²` up to the
+    /// middle and `1 - 2((x - end) / (end - start))²` after it
+    SShape { start: f64, end: f64 },
+    /// Smooth fall from 1 at `start` to 0 at `end`, the mirror of [`SShape`]
+    ///
+    /// [`SShape`]: MembershipFunction::SShape
+    ZShape { start: f64, end: f64 },
+    /// S-shaped rise from `bottom_left` to `top_left`, 1 up to `top_right` and Z-shaped fall to
+    /// `bottom_right`
+    PiShape {
+        bottom_left: f64,
+        top_left: f64,
+        top_right: f64,
+        bottom_right: f64,
+    },
+    /// `|s(rising, left) - s(falling, right)|` where `s(a, c)` is the [`Sigmoid`] of steepness
+    /// `a` crossing 0.5 at `c`
+    ///
+    /// [`Sigmoid`]: MembershipFunction::Sigmoid
+    SigmoidDifference {
+        left: f64,
+        rising: f64,
+        falling: f64,
+        right: f64,
+    },
+    /// `s(rising, left) * s(falling, right)` with the sigmoids of [`SigmoidDifference`]
+    ///
+    /// [`SigmoidDifference`]: MembershipFunction::SigmoidDifference
+    SigmoidProduct {
+        left: f64,
+        rising: f64,
+        falling: f64,
+        right: f64,
+    },
+    /// Left half of the gaussian `(mean_a, standard_deviation_a)` below `mean_a`, right half of
+    /// the gaussian `(mean_b, standard_deviation_b)` above `mean_b` and 1 in between
+    GaussianProduct {
+        mean_a: f64,
+        standard_deviation_a: f64,
+        mean_b: f64,
+        standard_deviation_b: f64,
+    },
+    /// `(1 + cos(2π (x - center) / width)) / 2` on `[center - width / 2, center + width / 2]`
+    /// and 0 elsewhere
+    Cosine { center: f64, width: f64 },
+    /// `(end - inflection) / (2 end - inflection - x)` below `end` and 1 from `end` when
+    /// `inflection < end`, mirrored when `inflection > end`. The membership is 0.5 at `inflection`
+    Concave { inflection: f64, end: f64 },
+    /// `exp(-|10 (x - center) / width|)`
+    Spike { center: f64, width: f64 },
+    /// 1 from `start` towards `direction`, which can be infinite, and 0 on the other side
+    Binary { start: f64, direction: f64 },
+    /// `value` everywhere, used by Takagi-Sugeno outputs
+    Constant { value: f64 },
+    /// `coefficients · inputs + constant`, used by Takagi-Sugeno outputs.
+    /// [`MembershipFunction::evaluate`] takes its value as the only input
+    Linear {
+        coefficients: Rc<[f64]>,
+        constant: f64,
+    },
+    /// Any other function, its shape cannot be inspected
+    Custom(Rc<dyn Fn(f64) -> f64>),
+}
+
+impl MembershipFunction {
+    /// Checked triangular function, `a <= b <= c` with `a < c`
+    pub fn triangular(a: f64, b: f64, c: f64) -> Result<Self> {
+        Self::Triangular { a, b, c }.checked()
+    }
+
+    /// Checked trapezoidal function, `a <= b <= c <= d` with `a < d`
+    pub fn trapezoidal(a: f64, b: f64, c: f64, d: f64) -> Result<Self> {
+        Self::Trapezoidal { a, b, c, d }.checked()
+    }
+
+    /// Checked gaussian function, `sigma > 0`
+    pub fn gaussian(c: f64, sigma: f64) -> Result<Self> {
+        Self::Gaussian { c, sigma }.checked()
+    }
+
+    /// Checked sigmoid function, `a != 0`
+    pub fn sigmoid(a: f64, c: f64) -> Result<Self> {
+        Self::Sigmoid { a, c }.checked()
+    }
+
+    /// Checked bell function, `a != 0`
+    pub fn bell(a: f64, b: f64, c: f64) -> Result<Self> {
+        Self::Bell { a, b, c }.checked()
+    }
+
+    /// Checked piecewise linear function, points sorted by `x` with `y` in `[0, 1]`
+    pub fn piecewise_linear(points: &[(f64, f64)]) -> Result<Self> {
+        Self::PiecewiseLinear {
+            points: points.into(),
+        }
+        .checked()
+    }
+
+    /// Membership of 1 at `value` only, as the vertical steps of a piecewise linear function
+    pub fn singleton(value: f64) -> Result<Self> {
+        Self::piecewise_linear(&[(value, 0.0), (value, 1.0), (value, 0.0)])
+    }
+
+    /// Checked ramp, `start != end`
+    pub fn ramp(start: f64, end: f64) -> Result<Self> {
+        Self::Ramp { start, end }.checked()
+    }
+
+    /// Checked rectangle, `start <= end`
+    pub fn rectangle(start: f64, end: f64) -> Result<Self> {
+        Self::Rectangle { start, end }.checked()
+    }
+
+    /// Checked S-shape, `start < end`
+    pub fn s_shape(start: f64, end: f64) -> Result<Self> {
+        Self::SShape { start, end }.checked()
+    }
+
+    /// Checked Z-shape, `start < end`
+    pub fn z_shape(start: f64, end: f64) -> Result<Self> {
+        Self::ZShape { start, end }.checked()
+    }
+
+    /// Checked Pi-shape, `bottom_left < top_left <= top_right < bottom_right`
+    pub fn pi_shape(
+        bottom_left: f64,
+        top_left: f64,
+        top_right: f64,
+        bottom_right: f64,
+    ) -> Result<Self> {
+        Self::PiShape {
+            bottom_left,
+            top_left,
+            top_right,
+            bottom_right,
+        }
+        .checked()
+    }
+
+    /// Checked difference of sigmoids
+    pub fn sigmoid_difference(left: f64, rising: f64, falling: f64, right: f64) -> Result<Self> {
+        Self::SigmoidDifference {
+            left,
+            rising,
+            falling,
+            right,
+        }
+        .checked()
+    }
+
+    /// Checked product of sigmoids
+    pub fn sigmoid_product(left: f64, rising: f64, falling: f64, right: f64) -> Result<Self> {
+        Self::SigmoidProduct {
+            left,
+            rising,
+            falling,
+            right,
+        }
+        .checked()
+    }
+
+    /// Checked product of gaussians, positive standard deviations and `mean_a <= mean_b`
+    pub fn gaussian_product(
+        mean_a: f64,
+        standard_deviation_a: f64,
+        mean_b: f64,
+        standard_deviation_b: f64,
+    ) -> Result<Self> {
+        Self::GaussianProduct {
+            mean_a,
+            standard_deviation_a,
+            mean_b,
+            standard_deviation_b,
+        }
+        .checked()
+    }
+
+    /// Checked cosine, `width > 0`
+    pub fn cosine(center: f64, width: f64) -> Result<Self> {
+        Self::Cosine { center, width }.checked()
+    }
+
+    /// Checked concave function, `inflection != end`
+    pub fn concave(inflection: f64, end: f64) -> Result<Self> {
+        Self::Concave { inflection, end }.checked()
+    }
+
+    /// Checked spike, `width > 0`
+    pub fn spike(center: f64, width: f64) -> Result<Self> {
+        Self::Spike { center, width }.checked()
+    }
+
+    /// Checked binary function, `direction != start`
+    pub fn binary(start: f64, direction: f64) -> Result<Self> {
+        Self::Binary { start, direction }.checked()
+    }
+
+    /// Checked constant
+    pub fn constant(value: f64) -> Result<Self> {
+        Self::Constant { value }.checked()
+    }
+
+    /// Checked linear function
+    pub fn linear(coefficients: &[f64], constant: f64) -> Result<Self> {
+        Self::Linear {
+            coefficients: coefficients.into(),
+            constant,
+        }
+        .checked()
+    }
+
+    /// Name of the shape
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Triangular { .. } => "Triangular",
+            Self::Trapezoidal { .. } => "Trapezoidal",
+            Self::Gaussian { .. } => "Gaussian",
+            Self::Sigmoid { .. } => "Sigmoid",
+            Self::Bell { .. } => "Bell",
+            Self::PiecewiseLinear { .. } => "PiecewiseLinear",
+            Self::Ramp { .. } => "Ramp",
+            Self::Rectangle { .. } => "Rectangle",
+            Self::SShape { .. } => "SShape",
+            Self::ZShape { .. } => "ZShape",
+            Self::PiShape { .. } => "PiShape",
+            Self::SigmoidDifference { .. } => "SigmoidDifference",
+            Self::SigmoidProduct { .. } => "SigmoidProduct",
+            Self::GaussianProduct { .. } => "GaussianProduct",
+            Self::Cosine { .. } => "Cosine",
+            Self::Concave { .. } => "Concave",
+            Self::Spike { .. } => "Spike",
+            Self::Binary { .. } => "Binary",
+            Self::Constant { .. } => "Constant",
+            Self::Linear { .. } => "Linear",
+            Self::Custom(_) => "Custom",
+        }
+    }
+
+    /// Returns the function if its parameters describe a valid shape
+    pub fn checked(self) -> Result<Self> {
+        let mut parameters = self.parameters();
+        if let Self::Binary { .. } = self {
+            // The direction of a binary function is usually infinite
+            parameters.pop();
+        }
+        if parameters.iter().any(|parameter| !parameter.is_finite()) {
+            bail!("{self:?} has a parameter that is not finite");
+        }
+        match self {
+            Self::Triangular { a, b, c } if !(a <= b && b <= c && a < c) => {
+                bail!("{self:?} needs a <= b <= c and a < c")
+            }
+            Self::Trapezoidal { a, b, c, d } if !(a <= b && b <= c && c <= d && a < d) => {
+                bail!("{self:?} needs a <= b <= c <= d and a < d")
+            }
+            Self::Gaussian { sigma, .. } if sigma <= 0.0 => bail!("{self:?} needs sigma > 0"),
+            Self::Sigmoid { a: 0.0, .. } | Self::Bell { a: 0.0, .. } => {
+                bail!("{self:?} needs a != 0")
+            }
+            Self::PiecewiseLinear { ref points } => {
+                if points.is_empty() {
+                    bail!("{self:?} needs at least one point");
+                }
+                if points.windows(2).any(|segment| segment[0].0 > segment[1].0) {
+                    bail!("{self:?} needs points sorted by x");
+                }
+                if points.iter().any(|&(_, y)| !(0.0..=1.0).contains(&y)) {
+                    bail!("{self:?} needs memberships between 0 and 1");
+                }
+            }
+            Self::Ramp { start, end } if start == end => bail!("{self:?} needs start != end"),
+            Self::Rectangle { start, end } if start > end => bail!("{self:?} needs start <= end"),
+            Self::SShape { start, end } | Self::ZShape { start, end } if start >= end => {
+                bail!("{self:?} needs start < end")
+            }
+            Self::PiShape {
+                bottom_left,
+                top_left,
+                top_right,
+                bottom_right,
+            } if !(bottom_left < top_left && top_left <= top_right && top_right < bottom_right) => {
+                bail!("{self:?} needs bottom_left < top_left <= top_right < bottom_right")
+            }
+            Self::GaussianProduct {
+                mean_a,
+                standard_deviation_a,
+                mean_b,
+                standard_deviation_b,
+            } if !(standard_deviation_a > 0.0 && standard_deviation_b > 0.0) || mean_a > mean_b => {
+                bail!("{self:?} needs positive standard deviations and mean_a <= mean_b")
+            }
+            Self::Cosine { width, .. } | Self::Spike { width, .. } if width <= 0.0 => {
+                bail!("{self:?} needs width > 0")
+            }
+            Self::Concave { inflection, end } if inflection == end => {
+                bail!("{self:?} needs inflection != end")
+            }
+            Self::Binary { start, direction } if direction == start || direction.is_nan() => {
+                bail!("{self:?} needs direction != start")
+            }
+            _ => {}
+        }
+        Ok(self)
+    }
+
+    /// Evaluate the membership of a value
+    pub fn evaluate(&self, x: f64) -> f64 {
+        match *self {
+            // The core is tested first so that a vertical side, `a == b` or `c == d`,
+            // keeps its membership of 1 and is never divided by
+            Self::Triangular { a, b, c } => {
+                if x < a || x > c {
+                    0.0
+                } else if x == b {
+                    1.0
+                } else if x < b {
+                    (x - a) / (b - a)
+                } else {
+                    (c - x) / (c - b)
+                }
+            }
+            Self::Trapezoidal { a, b, c, d } => {
+                if x < a || x > d {
+                    0.0
+                } else if b <= x && x <= c {
+                    1.0
+                } else if x < b {
+                    (x - a) / (b - a)
+                } else {
+                    (d - x) / (d - c)
+                }
+            }
+            Self::Gaussian { c, sigma } => (-((x - c).powi(2)) / (2.0 * sigma.powi(2))).exp(),
+            Self::Sigmoid { a, c } => logistic(a, c, x),
+            Self::Bell { a, b, c } => 1.0 / (1.0 + ((x - c) / a).abs().powf(2.0 * b)),
+            Self::PiecewiseLinear { ref points } => interpolate(points, x),
+            Self::Ramp { start, end } => ((x - start) / (end - start)).clamp(0.0, 1.0),
+            Self::Rectangle { start, end } => {
+                if start <= x && x <= end {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Self::SShape { start, end } => s_shape(start, end, x),
+            Self::ZShape { start, end } => 1.0 - s_shape(start, end, x),
+            Self::PiShape {
+                bottom_left,
+                top_left,
+                top_right,
+                bottom_right,
+            } => {
+                if x <= top_left {
+                    s_shape(bottom_left, top_left, x)
+                } else if x <= top_right {
+                    1.0
+                } else {
+                    1.0 - s_shape(top_right, bottom_right, x)
+                }
+            }
+            Self::SigmoidDifference {
+                left,
+                rising,
+                falling,
+                right,
+            } => (logistic(rising, left, x) - logistic(falling, right, x)).abs(),
+            Self::SigmoidProduct {
+                left,
+                rising,
+                falling,
+                right,
+            } => logistic(rising, left, x) * logistic(falling, right, x),
+            Self::GaussianProduct {
+                mean_a,
+                standard_deviation_a,
+                mean_b,
+                standard_deviation_b,
+            } => {
+                let half = |mean: f64, deviation: f64| {
+                    (-((x - mean).powi(2)) / (2.0 * deviation.powi(2))).exp()
+                };
+                let a = if x < mean_a {
+                    half(mean_a, standard_deviation_a)
+                } else {
+                    1.0
+                };
+                let b = if x > mean_b {
+                    half(mean_b, standard_deviation_b)
+                } else {
+                    1.0
+                };
+                a * b
+            }
+            Self::Cosine { center, width } => {
+                if (x - center).abs() > width / 2.0 {
+                    0.0
+                } else {
+                    0.5 * (1.0 + (2.0 * PI * (x - center) / width).cos())
+                }
+            }
+            Self::Concave { inflection, end } => {
+                if (inflection < end && x >= end) || (inflection > end && x <= end) {
+                    1.0
+                } else {
+                    (end - inflection) / (2.0 * end - inflection - x)
+                }
+            }
+            Self::Spike { center, width } => (-(10.0 * (x - center) / width).abs()).exp(),
+            Self::Binary { start, direction } => {
+                if (direction > start && x >= start) || (direction < start && x <= start) {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Self::Constant { value } => value,
+            Self::Linear { .. } => self.evaluate_inputs(&[x]),
+            Self::Custom(ref function) => function(x),
+        }
+    }
+
+    /// Evaluate the function on every input of a system, only [`Linear`] functions use more
+    /// than the first input
+    ///
+    /// [`Linear`]: MembershipFunction::Linear
+    pub fn evaluate_inputs(&self, inputs: &[f64]) -> f64 {
+        match self {
+            Self::Linear {
+                coefficients,
+                constant,
+            } => {
+                coefficients
+                    .iter()
+                    .zip(inputs)
+                    .map(|(coefficient, input)| coefficient * input)
+                    .sum::<f64>()
+                    + constant
+            }
+            _ => inputs.first().map_or(0.0, |&x| self.evaluate(x)),
+        }
+    }
+
+    /// Parameters of the shape in declaration order, the `x` and `y` of every point for piecewise
+    /// linear functions, the coefficients then the constant for linear ones and nothing for
+    /// custom ones
+    pub fn parameters(&self) -> Vec<f64> {
+        match *self {
+            Self::Triangular { a, b, c } | Self::Bell { a, b, c } => vec![a, b, c],
+            Self::Trapezoidal { a, b, c, d } => vec![a, b, c, d],
+            Self::Gaussian { c, sigma } => vec![c, sigma],
+            Self::Sigmoid { a, c } => vec![a, c],
+            Self::PiecewiseLinear { ref points } => {
+                points.iter().flat_map(|&(x, y)| [x, y]).collect()
+            }
+            Self::Ramp { start, end }
+            | Self::Rectangle { start, end }
+            | Self::SShape { start, end }
+            | Self::ZShape { start, end } => vec![start, end],
+            Self::PiShape {
+                bottom_left,
+                top_left,
+                top_right,
+                bottom_right,
+            } => vec![bottom_left, top_left, top_right, bottom_right],
+            Self::SigmoidDifference {
+                left,
+                rising,
+                falling,
+                right,
+            }
+            | Self::SigmoidProduct {
+                left,
+                rising,
+                falling,
+                right,
+            } => vec![left, rising, falling, right],
+            Self::GaussianProduct {
+                mean_a,
+                standard_deviation_a,
+                mean_b,
+                standard_deviation_b,
+            } => vec![mean_a, standard_deviation_a, mean_b, standard_deviation_b],
+            Self::Cosine { center, width } | Self::Spike { center, width } => vec![center, width],
+            Self::Concave { inflection, end } => vec![inflection, end],
+            Self::Binary { start, direction } => vec![start, direction],
+            Self::Constant { value } => vec![value],
+            Self::Linear {
+                ref coefficients,
+                constant,
+            } => coefficients.iter().copied().chain([constant]).collect(),
+            Self::Custom(_) => Vec::new(),
+        }
+    }
+
+    /// Points of the function when it is exactly piecewise linear
+    pub fn points(&self) -> Option<Vec<(f64, f64)>> {
+        match *self {
+            Self::Triangular { a, b, c } => Some(vec![(a, 0.0), (b, 1.0), (c, 0.0)]),
+            Self::Trapezoidal { a, b, c, d } => Some(vec![(a, 0.0), (b, 1.0), (c, 1.0), (d, 0.0)]),
+            Self::PiecewiseLinear { ref points } => Some(points.to_vec()),
+            Self::Ramp { start, end } if start < end => Some(vec![(start, 0.0), (end, 1.0)]),
+            Self::Ramp { start, end } => Some(vec![(end, 1.0), (start, 0.0)]),
+            Self::Rectangle { start, end } => {
+                Some(vec![(start, 0.0), (start, 1.0), (end, 1.0), (end, 0.0)])
+            }
+            Self::Binary { start, direction } if direction > start => {
+                Some(vec![(start, 0.0), (start, 1.0)])
+            }
+            Self::Binary { start, .. } => Some(vec![(start, 1.0), (start, 0.0)]),
+            _ => None,
+        }
+    }
+
+    /// Smallest interval outside of which the membership is 0, `None` for custom and linear
+    /// functions or functions that are 0 everywhere
+    pub fn support(&self) -> Option<(f64, f64)> {
+        let everywhere = Some((f64::NEG_INFINITY, f64::INFINITY));
+        match *self {
+            Self::Triangular { a, c, .. } => Some((a, c)),
+            Self::Trapezoidal { a, d, .. } => Some((a, d)),
+            Self::Gaussian { .. }
+            | Self::Sigmoid { .. }
+            | Self::Bell { .. }
+            | Self::SigmoidDifference { .. }
+            | Self::SigmoidProduct { .. }
+            | Self::GaussianProduct { .. }
+            | Self::Concave { .. }
+            | Self::Spike { .. } => everywhere,
+            Self::SShape { start, .. } => Some((start, f64::INFINITY)),
+            Self::ZShape { end, .. } => Some((f64::NEG_INFINITY, end)),
+            Self::PiShape {
+                bottom_left,
+                bottom_right,
+                ..
+            } => Some((bottom_left, bottom_right)),
+            Self::Cosine { center, width } => Some((center - width / 2.0, center + width / 2.0)),
+            Self::Constant { value } if value > 0.0 => everywhere,
+            Self::Constant { .. } | Self::Linear { .. } | Self::Custom(_) => None,
+            _ => interval(&self.points()?, |y| y > 0.0, true),
+        }
+    }
+
+    /// Interval where the membership is 1, `None` for custom and linear functions or when it
+    /// never is
+    pub fn core(&self) -> Option<(f64, f64)> {
+        match *self {
+            Self::Triangular { b, .. } => Some((b, b)),
+            Self::Trapezoidal { b, c, .. } => Some((b, c)),
+            Self::Gaussian { c, .. } | Self::Bell { c, .. } => Some((c, c)),
+            Self::Cosine { center, .. } | Self::Spike { center, .. } => Some((center, center)),
+            Self::SShape { end, .. } => Some((end, f64::INFINITY)),
+            Self::ZShape { start, .. } => Some((f64::NEG_INFINITY, start)),
+            Self::PiShape {
+                top_left,
+                top_right,
+                ..
+            } => Some((top_left, top_right)),
+            Self::GaussianProduct { mean_a, mean_b, .. } => Some((mean_a, mean_b)),
+            Self::Concave { inflection, end } if inflection < end => Some((end, f64::INFINITY)),
+            Self::Concave { end, .. } => Some((f64::NEG_INFINITY, end)),
+            Self::Constant { value: 1.0 } => Some((f64::NEG_INFINITY, f64::INFINITY)),
+            Self::Sigmoid { .. }
+            | Self::SigmoidDifference { .. }
+            | Self::SigmoidProduct { .. }
+            | Self::Constant { .. }
+            | Self::Linear { .. }
+            | Self::Custom(_) => None,
+            _ => interval(&self.points()?, |y| y == 1.0, false),
+        }
+    }
+
+    /// Highest membership of the function, `None` when it has no closed form as for custom,
+    /// linear and sigmoid combinations
+    pub fn height(&self) -> Option<f64> {
+        match *self {
+            Self::PiecewiseLinear { ref points } => points.iter().map(|&(_, y)| y).reduce(f64::max),
+            Self::Constant { value } => Some(value),
+            Self::SigmoidDifference { .. }
+            | Self::SigmoidProduct { .. }
+            | Self::Linear { .. }
+            | Self::Custom(_) => None,
+            _ => Some(1.0),
+        }
+    }
+
+    /// Whether the membership only rises or only falls, as Tsukamoto consequents must
+    pub fn is_monotonic(&self) -> bool {
+        matches!(
+            self,
+            Self::Ramp { .. }
+                | Self::Sigmoid { .. }
+                | Self::SShape { .. }
+                | Self::ZShape { .. }
+                | Self::Concave { .. }
+        )
+    }
+
+    /// Value whose membership is `y`, clamped to `[0, 1]`, `None` when the function is not
+    /// monotonic. Sigmoids and concave functions only reach 0 or 1 at an infinite value
+    pub fn inverse(&self, y: f64) -> Option<f64> {
+        let y = y.clamp(0.0, 1.0);
+        Some(match *self {
+            Self::Ramp { start, end } => start + y * (end - start),
+            Self::Sigmoid { a, c } => c - (1.0 / y - 1.0).ln() / a,
+            Self::SShape { start, end } => inverse_s_shape(start, end, y),
+            Self::ZShape { start, end } => inverse_s_shape(start, end, 1.0 - y),
+            Self::Concave { end, .. } if y == 1.0 => end,
+            Self::Concave { inflection, end } => 2.0 * end - inflection - (end - inflection) / y,
+            _ => return None,
+        })
+    }
+}
+
+/// Value of membership `y` to the rise from 0 at `start` to 1 at `end`
+fn inverse_s_shape(start: f64, end: f64, y: f64) -> f64 {
+    if y <= 0.5 {
+        start + (end - start) * (y / 2.0).sqrt()
+    } else {
+        end - (end - start) * ((1.0 - y) / 2.0).sqrt()
+    }
+}
+
+/// Logistic curve of steepness `a` crossing 0.5 at `c`
+fn logistic(a: f64, c: f64, x: f64) -> f64 {
+    1.0 / (1.0 + (-a * (x - c)).exp())
+}
+
+/// Smooth rise from 0 at `start` to 1 at `end`
+fn s_shape(start: f64, end: f64, x: f64) -> f64 {
+    if x <= start {
+        0.0
+    } else if x <= (start + end) / 2.0 {
+        2.0 * ((x - start) / (end - start)).powi(2)
+    } else if x < end {
+        1.0 - 2.0 * ((x - end) / (end - start)).powi(2)
+    } else {
+        1.0
+    }
+}
+
+/// Evaluates a piecewise linear function at `x`.
+///
+/// The memberships of the first and last points are held outside of the points, which gives left
+/// and right shoulders. Points sharing the same `x` make a vertical step whose highest membership
+/// is taken at that `x`, so `(5, 0) (5, 1) (5, 0)` is a singleton at 5.
+pub fn interpolate(points: &[(f64, f64)], x: f64) -> f64 {
+    let (Some(&(first_x, first_y)), Some(&(last_x, last_y))) = (points.first(), points.last())
+    else {
+        return 0.0;
+    };
+    if x < first_x {
+        return first_y;
+    }
+    if x > last_x {
+        return last_y;
+    }
+    let step = points
+        .iter()
+        .filter(|&&(px, _)| px == x)
+        .map(|&(_, y)| y)
+        .reduce(f64::max);
+    if let Some(y) = step {
+        return y;
+    }
+    points
+        .windows(2)
+        .find(|segment| segment[0].0 < x && x < segment[1].0)
+        .map(|segment| {
+            let ((x0, y0), (x1, y1)) = (segment[0], segment[1]);
+            y0 + (y1 - y0) * (x - x0) / (x1 - x0)
+        })
+        .unwrap_or(0.0)
+}
+
+/// Interval of a piecewise linear function covering the points whose membership matches, up to
+/// the neighbouring points when `widen`. It is open ended when the first or last point matches
+/// since its membership is held
+fn interval(
+    points: &[(f64, f64)],
+    matches: impl Fn(f64) -> bool,
+    widen: bool,
+) -> Option<(f64, f64)> {
+    let first = points.iter().position(|&(_, y)| matches(y))?;
+    let last = points.iter().rposition(|&(_, y)| matches(y))?;
+    let lower = match first {
+        0 => f64::NEG_INFINITY,
+        _ if widen => points[first - 1].0,
+        _ => points[first].0,
+    };
+    let upper = match last {
+        last if last == points.len() - 1 => f64::INFINITY,
+        _ if widen => points[last + 1].0,
+        _ => points[last].0,
+    };
+    Some((lower, upper))
+}
+
+impl fmt::Debug for MembershipFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PiecewiseLinear { points } => write!(f, "PiecewiseLinear({points:?})"),
+            Self::Custom(_) => write!(f, "Custom"),
+            _ => {
+                let parameters: Vec<_> = self.parameters().iter().map(f64::to_string).collect();
+                write!(f, "{}({})", self.name(), parameters.join(", "))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checked_constructors() {
+        assert!(MembershipFunction::triangular(0.0, 5.0, 10.0).is_ok());
+        assert!(MembershipFunction::triangular(0.0, 0.0, 10.0).is_ok());
+        assert!(MembershipFunction::triangular(5.0, 0.0, 10.0).is_err());
+        assert!(MembershipFunction::triangular(0.0, 0.0, 0.0).is_err());
+        assert!(MembershipFunction::trapezoidal(0.0, 2.0, 1.0, 3.0).is_err());
+        assert!(MembershipFunction::gaussian(0.0, 0.0).is_err());
+        assert!(MembershipFunction::gaussian(f64::NAN, 1.0).is_err());
+        assert!(MembershipFunction::bell(0.0, 1.0, 0.0).is_err());
+        let flat = MembershipFunction::sigmoid(0.0, 5.0).err().unwrap().to_string();
+        assert_eq!(flat, "Sigmoid(0, 5) needs a != 0");
+        assert!(MembershipFunction::piecewise_linear(&[(1.0, 0.0), (0.0, 1.0)]).is_err());
+        assert!(MembershipFunction::piecewise_linear(&[(0.0, 2.0)]).is_err());
+        assert!(MembershipFunction::piecewise_linear(&[]).is_err());
+    }
+
+    #[test]
+    fn test_parameters() {
+        let trapezoidal = MembershipFunction::trapezoidal(0.0, 1.0, 2.0, 3.0).unwrap();
+        assert_eq!(trapezoidal.parameters(), vec![0.0, 1.0, 2.0, 3.0]);
+        let points = MembershipFunction::piecewise_linear(&[(0.0, 1.0), (1.0, 0.0)]).unwrap();
+        assert_eq!(points.parameters(), vec![0.0, 1.0, 1.0, 0.0]);
+        assert!(MembershipFunction::Custom(Rc::new(|x| x))
+            .parameters()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_support_core_height() {
+        let triangular = MembershipFunction::triangular(0.0, 5.0, 10.0).unwrap();
+        assert_eq!(triangular.support(), Some((0.0, 10.0)));
+        assert_eq!(triangular.core(), Some((5.0, 5.0)));
+        assert_eq!(triangular.height(), Some(1.0));
+
+        let gaussian = MembershipFunction::gaussian(2.0, 1.0).unwrap();
+        assert_eq!(gaussian.support(), Some((f64::NEG_INFINITY, f64::INFINITY)));
+        assert_eq!(gaussian.core(), Some((2.0, 2.0)));
+        assert_eq!(MembershipFunction::sigmoid(1.0, 0.0).unwrap().core(), None);
+
+        let shoulder =
+            MembershipFunction::piecewise_linear(&[(-20.0, 1.0), (-10.0, 1.0), (0.0, 0.0)])
+                .unwrap();
+        assert_eq!(shoulder.support(), Some((f64::NEG_INFINITY, 0.0)));
+        assert_eq!(shoulder.core(), Some((f64::NEG_INFINITY, -10.0)));
+        let low =
+            MembershipFunction::piecewise_linear(&[(0.0, 0.0), (5.0, 0.5), (10.0, 0.0)]).unwrap();
+        assert_eq!(low.support(), Some((0.0, 10.0)));
+        assert_eq!(low.core(), None);
+        assert_eq!(low.height(), Some(0.5));
+
+        let custom = MembershipFunction::Custom(Rc::new(|_| 1.0));
+        assert_eq!(custom.support(), None);
+        assert_eq!(custom.height(), None);
+    }
+
+    #[test]
+    fn test_degenerate_shapes() {
+        let left = MembershipFunction::triangular(0.0, 0.0, 10.0).unwrap();
+        assert_eq!(left.evaluate(-1.0), 0.0);
+        assert_eq!(left.evaluate(0.0), 1.0);
+        assert_eq!(left.evaluate(5.0), 0.5);
+        assert_eq!(left.evaluate(10.0), 0.0);
+
+        let right = MembershipFunction::triangular(0.0, 10.0, 10.0).unwrap();
+        assert_eq!(right.evaluate(0.0), 0.0);
+        assert_eq!(right.evaluate(5.0), 0.5);
+        assert_eq!(right.evaluate(10.0), 1.0);
+        assert_eq!(right.evaluate(11.0), 0.0);
+
+        let shoulder = MembershipFunction::trapezoidal(0.0, 0.0, 5.0, 10.0).unwrap();
+        assert_eq!(shoulder.evaluate(0.0), 1.0);
+        assert_eq!(shoulder.evaluate(5.0), 1.0);
+        assert_eq!(shoulder.evaluate(7.5), 0.5);
+        let rectangle = MembershipFunction::trapezoidal(0.0, 0.0, 5.0, 5.0).unwrap();
+        assert_eq!(rectangle.evaluate(5.0), 1.0);
+        assert_eq!(rectangle.evaluate(5.1), 0.0);
+    }
+
+    #[test]
+    fn test_points_match_evaluation() {
+        let triangular = MembershipFunction::triangular(0.0, 4.0, 10.0).unwrap();
+        let points = MembershipFunction::piecewise_linear(&triangular.points().unwrap()).unwrap();
+        for x in -2..=12 {
+            let x = x as f64;
+            assert!((triangular.evaluate(x) - points.evaluate(x)).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_interpolate_steps() {
+        let step = [(0.0, 0.0), (5.0, 0.0), (5.0, 1.0), (10.0, 1.0)];
+        assert_eq!(interpolate(&step, 4.0), 0.0);
+        assert_eq!(interpolate(&step, 5.0), 1.0);
+        assert_eq!(interpolate(&step, 12.0), 1.0);
+
+        let singleton = MembershipFunction::singleton(5.0).unwrap();
+        assert_eq!(singleton.evaluate(5.0), 1.0);
+        assert_eq!(singleton.evaluate(4.9), 0.0);
+        assert_eq!(singleton.evaluate(5.1), 0.0);
+        assert_eq!(singleton.support(), Some((5.0, 5.0)));
+        assert_eq!(singleton.core(), Some((5.0, 5.0)));
+
+        assert_eq!(interpolate(&[(3.0, 0.25)], -1.0), 0.25);
+        assert_eq!(interpolate(&[], 1.0), 0.0);
+    }
+
+    fn assert_close(function: &MembershipFunction, expected: &[(f64, f64)]) {
+        for &(x, y) in expected {
+            let value = function.evaluate(x);
+            assert!(
+                (value - y).abs() < 1e-9,
+                "{function:?} at {x} is {value}, not {y}"
+            );
+        }
+    }
+
+    /// Values computed by fuzzylite 6 for the same terms
+    #[test]
+    fn test_fuzzylite_values() {
+        use MembershipFunction as F;
+        assert_close(
+            &F::ramp(0.25, 0.75).unwrap(),
+            &[(0.0, 0.0), (0.3, 0.1), (0.5, 0.5), (1.0, 1.0)],
+        );
+        assert_close(
+            &F::ramp(0.75, 0.25).unwrap(),
+            &[(0.0, 1.0), (0.5, 0.5), (1.0, 0.0)],
+        );
+        assert_close(
+            &F::rectangle(0.25, 0.75).unwrap(),
+            &[(0.2, 0.0), (0.25, 1.0), (0.75, 1.0), (0.8, 0.0)],
+        );
+        assert_close(
+            &F::s_shape(0.0, 1.0).unwrap(),
+            &[
+                (-1.0, 0.0),
+                (0.25, 0.125),
+                (0.5, 0.5),
+                (0.75, 0.875),
+                (2.0, 1.0),
+            ],
+        );
+        assert_close(
+            &F::z_shape(0.0, 1.0).unwrap(),
+            &[(-1.0, 1.0), (0.25, 0.875), (0.75, 0.125), (2.0, 0.0)],
+        );
+        assert_close(
+            &F::pi_shape(0.0, 0.25, 0.5, 1.0).unwrap(),
+            &[(0.1, 0.32), (0.4, 1.0), (0.8, 0.32), (1.0, 0.0)],
+        );
+        assert_close(
+            &F::sigmoid_difference(0.25, 20.0, 20.0, 0.75).unwrap(),
+            &[(0.25, 0.49995460213129755), (0.5, 0.9866142981514304)],
+        );
+        assert_close(
+            &F::sigmoid_product(0.25, 20.0, -20.0, 0.75).unwrap(),
+            &[(0.25, 0.4999773010656488), (0.5, 0.9866590924049252)],
+        );
+        assert_close(
+            &F::gaussian_product(0.4, 0.1, 0.6, 0.1).unwrap(),
+            &[
+                (0.3, 0.6065306597126334),
+                (0.5, 1.0),
+                (0.8, 0.1353352832366127),
+            ],
+        );
+        assert_close(
+            &F::cosine(0.5, 1.0).unwrap(),
+            &[(0.25, 0.5), (0.5, 1.0), (1.2, 0.0)],
+        );
+        assert_close(
+            &F::concave(0.5, 0.75).unwrap(),
+            &[(0.25, 1.0 / 3.0), (0.5, 0.5), (0.8, 1.0)],
+        );
+        assert_close(
+            &F::concave(0.5, 0.25).unwrap(),
+            &[(0.75, 1.0 / 3.0), (0.5, 0.5), (0.1, 1.0)],
+        );
+        assert_close(
+            &F::spike(0.5, 1.0).unwrap(),
+            &[(0.5, 1.0), (0.6, 0.36787944117144233)],
+        );
+        assert_close(
+            &F::binary(0.5, f64::INFINITY).unwrap(),
+            &[(0.4, 0.0), (0.5, 1.0), (0.9, 1.0)],
+        );
+        assert_close(
+            &F::binary(0.5, f64::NEG_INFINITY).unwrap(),
+            &[(0.4, 1.0), (0.6, 0.0)],
+        );
+        assert_close(&F::constant(0.25).unwrap(), &[(-10.0, 0.25), (10.0, 0.25)]);
+
+        let linear = F::linear(&[2.0, -1.0], 0.5).unwrap();
+        assert_eq!(linear.evaluate_inputs(&[1.0, 3.0]), -0.5);
+        assert_eq!(linear.parameters(), vec![2.0, -1.0, 0.5]);
+    }
+
+    #[test]
+    fn test_fuzzylite_validation() {
+        use MembershipFunction as F;
+        assert!(F::ramp(1.0, 1.0).is_err());
+        assert!(F::rectangle(1.0, 0.0).is_err());
+        assert!(F::s_shape(1.0, 0.0).is_err());
+        assert!(F::z_shape(0.0, 0.0).is_err());
+        assert!(F::pi_shape(0.0, 0.5, 0.25, 1.0).is_err());
+        assert!(F::gaussian_product(0.6, 0.1, 0.4, 0.1).is_err());
+        assert!(F::gaussian_product(0.4, 0.0, 0.6, 0.1).is_err());
+        assert!(F::cosine(0.0, 0.0).is_err());
+        assert!(F::spike(0.0, -1.0).is_err());
+        assert!(F::concave(0.5, 0.5).is_err());
+        assert!(F::binary(0.5, 0.5).is_err());
+        assert!(F::binary(f64::INFINITY, 0.5).is_err());
+        assert!(F::constant(f64::NAN).is_err());
+        assert!(F::linear(&[f64::INFINITY], 0.0).is_err());
+    }
+
+    #[test]
+    fn test_inverse() {
+        use MembershipFunction as F;
+        for function in [
+            F::ramp(0.25, 0.75).unwrap(),
+            F::ramp(0.75, 0.25).unwrap(),
+            F::sigmoid(20.0, 0.5).unwrap(),
+            F::s_shape(0.0, 1.0).unwrap(),
+            F::z_shape(0.0, 1.0).unwrap(),
+            F::concave(0.5, 0.75).unwrap(),
+            F::concave(0.5, 0.25).unwrap(),
+        ] {
+            assert!(function.is_monotonic());
+            for y in [0.1, 0.25, 0.5, 0.9] {
+                let x = function.inverse(y).unwrap();
+                assert!(
+                    (function.evaluate(x) - y).abs() < 1e-9,
+                    "{function:?} at {x} is not {y}"
+                );
+            }
+        }
+        assert_eq!(F::ramp(0.0, 2.0).unwrap().inverse(1.5), Some(2.0));
+        assert_eq!(F::concave(0.5, 0.75).unwrap().inverse(1.0), Some(0.75));
+        assert_eq!(
+            F::sigmoid(1.0, 0.0).unwrap().inverse(0.0),
+            Some(f64::NEG_INFINITY)
+        );
+        let triangular = F::triangular(0.0, 1.0, 2.0).unwrap();
+        assert!(!triangular.is_monotonic());
+        assert_eq!(triangular.inverse(0.5), None);
+    }
+
+    #[test]
+    fn test_fuzzylite_shapes() {
+        use MembershipFunction as F;
+        let rectangle = F::rectangle(0.25, 0.75).unwrap();
+        assert_eq!(rectangle.support(), Some((0.25, 0.75)));
+        assert_eq!(rectangle.core(), Some((0.25, 0.75)));
+        let points = F::piecewise_linear(&rectangle.points().unwrap()).unwrap();
+        assert_close(
+            &points,
+            &[(0.2, 0.0), (0.25, 1.0), (0.5, 1.0), (0.75, 1.0), (0.8, 0.0)],
+        );
+
+        let ramp = F::ramp(0.75, 0.25).unwrap();
+        assert_eq!(ramp.support(), Some((f64::NEG_INFINITY, 0.75)));
+        assert_eq!(ramp.core(), Some((f64::NEG_INFINITY, 0.25)));
+        assert_eq!(
+            F::binary(0.5, f64::INFINITY).unwrap().core(),
+            Some((0.5, f64::INFINITY))
+        );
+        assert_eq!(
+            F::pi_shape(0.0, 0.25, 0.5, 1.0).unwrap().support(),
+            Some((0.0, 1.0))
+        );
+        assert_eq!(
+            F::sigmoid_product(0.25, 20.0, -20.0, 0.75)
+                .unwrap()
+                .height(),
+            None
+        );
+        assert_eq!(
+            format!("{:?}", F::cosine(0.5, 1.0).unwrap()),
+            "Cosine(0.5, 1)"
+        );
+    }
+}
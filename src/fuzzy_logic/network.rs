@@ -0,0 +1,309 @@
+//! Networks of named fuzzy systems, the outputs of a system feeding the inputs of the same name
+//! of the others, as a behaviour score and an identity assurance feeding an overall trust.
+//!
+//! The systems are evaluated in topological order, each after the systems computing its inputs.
+use anyhow::{bail, Result};
+use serde::Serialize;
+
+use crate::fuzzy_logic::control_language::compile_fcl_blocks;
+use crate::fuzzy_logic::inference::{FuzzySystem, Outputs, Trace};
+
+/// Fuzzy systems chained by the names of their variables
+pub struct FuzzyNetwork {
+    /// Systems in topological order
+    systems: Vec<(String, Box<dyn FuzzySystem>)>,
+    /// Inputs of the systems that no system computes, in the order of the systems
+    inputs: Vec<String>,
+}
+
+/// Traces of the systems of a network, in the order they were evaluated
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NetworkTrace {
+    pub systems: Vec<SystemTrace>,
+}
+
+/// Trace of the inference of a system of a network
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SystemTrace {
+    pub system: String,
+    pub trace: Trace,
+}
+
+impl FuzzyNetwork {
+    /// Network of the named `systems`, an input of a system taking the value of the output of
+    /// the same name of another.
+    /// Fails when two systems share a name or an output, or when systems depend on each other
+    /// in a cycle
+    pub fn new(systems: Vec<(&str, Box<dyn FuzzySystem>)>) -> Result<Self> {
+        for (index, (name, _)) in systems.iter().enumerate() {
+            if systems[..index].iter().any(|(other, _)| other == name) {
+                bail!("system {name} is declared twice");
+            }
+        }
+        // System computing each output
+        let mut producers: Vec<(&str, usize)> = Vec::new();
+        for (index, (name, system)) in systems.iter().enumerate() {
+            for output in system.outputs() {
+                if let Some(&(_, other)) = producers.iter().find(|(o, _)| *o == output.name) {
+                    bail!(
+                        "{} is an output of both {} and {name}",
+                        output.name,
+                        systems[other].0
+                    );
+                }
+                producers.push((&output.name, index));
+            }
+        }
+        let producer = |input: &str| {
+            producers
+                .iter()
+                .find(|(output, _)| *output == input)
+                .map(|&(_, index)| index)
+        };
+        // Systems computing the inputs of each system
+        let dependencies: Vec<Vec<usize>> = systems
+            .iter()
+            .map(|(_, system)| {
+                system
+                    .inputs()
+                    .iter()
+                    .filter_map(|input| producer(&input.name))
+                    .collect()
+            })
+            .collect();
+
+        // Kahn's algorithm, the systems whose inputs are all known in their declared order
+        let mut order = Vec::new();
+        let mut done = vec![false; systems.len()];
+        while let Some(next) = (0..systems.len())
+            .find(|&index| !done[index] && dependencies[index].iter().all(|&d| done[d]))
+        {
+            done[next] = true;
+            order.push(next);
+        }
+        if let Some(start) = done.iter().position(|&done| !done) {
+            // Every system left depends on another one left, going up the dependencies loops
+            let mut path = vec![start];
+            let cycle = loop {
+                let last = *path.last().unwrap();
+                let Some(&next) = dependencies[last].iter().find(|&&d| !done[d]) else {
+                    unreachable!("system {} has all its inputs", systems[last].0);
+                };
+                if let Some(position) = path.iter().position(|&index| index == next) {
+                    break &path[position..];
+                }
+                path.push(next);
+            };
+            let mut names: Vec<_> = cycle.iter().rev().map(|&index| systems[index].0).collect();
+            names.push(names[0]);
+            bail!("systems {} depend on each other", names.join(" -> "));
+        }
+
+        let mut inputs: Vec<String> = Vec::new();
+        for (_, system) in &systems {
+            for input in system.inputs() {
+                if producer(&input.name).is_none() && !inputs.contains(&input.name) {
+                    inputs.push(input.name.clone());
+                }
+            }
+        }
+        let mut systems: Vec<_> = systems.into_iter().map(Some).collect();
+        let systems = order
+            .into_iter()
+            .filter_map(|index| systems[index].take())
+            .map(|(name, system)| (name.to_string(), system))
+            .collect();
+        Ok(Self { systems, inputs })
+    }
+
+    /// Network of the [`Mamdani`] systems of every function block of an FCL program, named as
+    /// their block
+    ///
+    /// [`Mamdani`]: crate::fuzzy_logic::inference::Mamdani
+    pub fn from_fcl(program: &str) -> Result<Self> {
+        let blocks = compile_fcl_blocks(program)?;
+        let names: Vec<_> = blocks.iter().map(|(name, _)| name.clone()).collect();
+        let systems = names
+            .iter()
+            .zip(blocks)
+            .map(|(name, (_, mamdani))| (name.as_str(), Box::new(mamdani) as Box<dyn FuzzySystem>))
+            .collect();
+        Self::new(systems)
+    }
+
+    /// Names of the inputs of the systems that no system computes
+    pub fn inputs(&self) -> &[String] {
+        &self.inputs
+    }
+
+    /// Names of the systems in the order they are evaluated
+    pub fn order(&self) -> impl Iterator<Item = &str> {
+        self.systems.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// System named `name`
+    pub fn system(&self, name: &str) -> Option<&dyn FuzzySystem> {
+        self.systems
+            .iter()
+            .find(|(system, _)| system == name)
+            .map(|(_, system)| system.as_ref())
+    }
+
+    /// Crisp value of the outputs of every system, in the order they are evaluated, from the
+    /// value of each input of the network by name
+    pub fn evaluate(&self, input_values: &[(&str, f64)]) -> Result<Outputs> {
+        self.run(input_values, |system, values| {
+            (system.infer_outputs(values), None)
+        })
+        .map(|(outputs, _)| outputs)
+    }
+
+    /// Crisp value of the outputs of every system with the trace of each system
+    pub fn evaluate_traced(&self, input_values: &[(&str, f64)]) -> Result<(Outputs, NetworkTrace)> {
+        self.run(input_values, |system, values| {
+            let (outputs, trace) = system.infer_traced(values);
+            (outputs, Some(trace))
+        })
+    }
+
+    fn run(
+        &self,
+        input_values: &[(&str, f64)],
+        infer: impl Fn(&dyn FuzzySystem, &[f64]) -> (Outputs, Option<Trace>),
+    ) -> Result<(Outputs, NetworkTrace)> {
+        for (name, _) in input_values {
+            if !self.inputs.iter().any(|input| input == name) {
+                bail!("{name} is not an input of the network");
+            }
+        }
+        let mut known: Vec<(String, f64)> = Vec::new();
+        for input in &self.inputs {
+            let Some(&(_, value)) = input_values.iter().find(|(name, _)| name == input) else {
+                bail!("input {input} has no value");
+            };
+            known.push((input.clone(), value));
+        }
+
+        let mut outputs = Outputs::default();
+        let mut traces = Vec::new();
+        for (name, system) in &self.systems {
+            let values: Vec<_> = system
+                .inputs()
+                .iter()
+                .map(|input| {
+                    known
+                        .iter()
+                        .find(|(known, _)| *known == input.name)
+                        .map_or(f64::NAN, |&(_, value)| value)
+                })
+                .collect();
+            let (mut computed, trace) = infer(system.as_ref(), &values);
+            known.extend(
+                computed
+                    .iter()
+                    .map(|(output, value)| (output.to_string(), value)),
+            );
+            outputs.append(&mut computed);
+            if let Some(trace) = trace {
+                traces.push(SystemTrace {
+                    system: name.clone(),
+                    trace,
+                });
+            }
+        }
+        Ok((outputs, NetworkTrace { systems: traces }))
+    }
+}
+
+impl NetworkTrace {
+    /// Trace of the system named `name`
+    pub fn system(&self, name: &str) -> Option<&Trace> {
+        self.systems
+            .iter()
+            .find(|system| system.system == name)
+            .map(|system| &system.trace)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Function block of `output` from the inputs `a` and `b`, high when both are
+    fn block(name: &str, a: &str, b: &str, output: &str) -> String {
+        let terms = "TERM low := (0, 1) (10, 0);\n  TERM high := (0, 0) (10, 1);";
+        format!(
+            "FUNCTION_BLOCK {name}
+VAR_INPUT {a}: REAL; {b}: REAL; END_VAR
+VAR_OUTPUT {output}: REAL; END_VAR
+FUZZIFY {a}\n  {terms}\nEND_FUZZIFY
+FUZZIFY {b}\n  {terms}\nEND_FUZZIFY
+DEFUZZIFY {output}\n  {terms}\n  METHOD: COG;\n  RANGE := (0 .. 10);\nEND_DEFUZZIFY
+RULEBLOCK No1
+  AND: MIN;
+  ACCU: MAX;
+  RULE 1 : IF {a} IS low THEN {output} IS low;
+  RULE 2 : IF {b} IS low THEN {output} IS low;
+  RULE 3 : IF {a} IS high AND {b} IS high THEN {output} IS high;
+END_RULEBLOCK
+END_FUNCTION_BLOCK
+"
+        )
+    }
+
+    fn trust() -> String {
+        block("overall", "behaviour", "identity", "trust")
+            + &block("behaviour", "payments", "disputes", "behaviour")
+            + &block("identity", "documents", "age", "identity")
+    }
+
+    #[test]
+    fn test_network() {
+        let network = FuzzyNetwork::from_fcl(&trust()).unwrap();
+        assert_eq!(
+            network.order().collect::<Vec<_>>(),
+            vec!["behaviour", "identity", "overall"]
+        );
+        assert_eq!(
+            network.inputs(),
+            ["payments", "disputes", "documents", "age"]
+        );
+
+        let inputs = [
+            ("payments", 9.0),
+            ("disputes", 8.0),
+            ("documents", 7.0),
+            ("age", 6.0),
+        ];
+        let (outputs, trace) = network.evaluate_traced(&inputs).unwrap();
+        assert_eq!(outputs, network.evaluate(&inputs).unwrap());
+        assert_eq!(outputs.len(), 3);
+        let overall = network.system("overall").unwrap();
+        let intermediate = [outputs["behaviour"], outputs["identity"]];
+        assert_eq!(outputs["trust"], overall.infer(&intermediate));
+
+        // The trace of the last system starts from the outputs of the others
+        let overall = trace.system("overall").unwrap();
+        assert_eq!(overall.inputs[0].value, outputs["behaviour"]);
+        assert_eq!(overall.inputs[1].value, outputs["identity"]);
+        let json = serde_json::to_value(&trace).unwrap();
+        assert_eq!(json["systems"][2]["system"], "overall");
+    }
+
+    #[test]
+    fn test_network_errors() {
+        let error = |program: &str| FuzzyNetwork::from_fcl(program).err().unwrap().to_string();
+        let cycle = block("a", "x", "z", "y") + &block("b", "y", "w", "z");
+        assert_eq!(error(&cycle), "systems b -> a -> b depend on each other");
+        let shared = block("a", "x", "w", "y") + &block("b", "x", "w", "y");
+        assert_eq!(error(&shared), "y is an output of both a and b");
+        let twice = block("a", "x", "w", "y") + &block("a", "x", "w", "z");
+        assert_eq!(error(&twice), "system a is declared twice");
+
+        let network = FuzzyNetwork::from_fcl(&trust()).unwrap();
+        let error = network.evaluate(&[("payments", 1.0)]).unwrap_err();
+        assert_eq!(error.to_string(), "input disputes has no value");
+        let error = network.evaluate(&[("trust", 1.0)]).unwrap_err();
+        assert_eq!(error.to_string(), "trust is not an input of the network");
+    }
+}
@@ -0,0 +1,248 @@
+//! Triangular norms combining memberships, the T-norms conjunct and the S-norms disjunct.
+//!
+//! The implementations and their names follow fuzzylite.
+use std::rc::Rc;
+
+/// Conjunction of two memberships, commutative, associative, monotonic and with 1 as identity
+pub trait TNorm {
+    fn name(&self) -> &'static str;
+    fn compute(&self, a: f64, b: f64) -> f64;
+}
+
+/// Disjunction of two memberships, commutative, associative, monotonic and with 0 as identity
+pub trait SNorm {
+    fn name(&self) -> &'static str;
+    fn compute(&self, a: f64, b: f64) -> f64;
+}
+
+/// `min(a, b)`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Minimum;
+
+/// `a * b`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AlgebraicProduct;
+
+/// Łukasiewicz T-norm `max(0, a + b - 1)`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BoundedDifference;
+
+/// `min(a, b)` when `max(a, b) = 1`, 0 otherwise
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DrasticProduct;
+
+/// `a b / (2 - (a + b - a b))`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EinsteinProduct;
+
+/// `a b / (a + b - a b)`, 0 when `a = b = 0`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HamacherProduct;
+
+/// `min(a, b)` when `a + b > 1`, 0 otherwise
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NilpotentMinimum;
+
+/// `max(a, b)`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Maximum;
+
+/// Probabilistic sum `a + b - a b`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AlgebraicSum;
+
+/// `min(1, a + b)`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BoundedSum;
+
+/// `max(a, b)` when `min(a, b) = 0`, 1 otherwise
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DrasticSum;
+
+/// `(a + b) / (1 + a b)`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EinsteinSum;
+
+/// `(a + b - 2 a b) / (1 - a b)`, 1 when `a = b = 1`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HamacherSum;
+
+/// `max(a, b)` when `a + b < 1`, 1 otherwise
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NilpotentMaximum;
+
+/// `(a + b) / max(1, a, b)`.
+/// As in fuzzylite the result is not clamped and exceeds 1 when `a + b > 1`,
+/// `NormalizedSum(0.5, 0.75) == 1.25`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NormalizedSum;
+
+macro_rules! norm {
+    ($norm:ident, $trait:ident, |$a:ident, $b:ident| $compute:expr) => {
+        impl $trait for $norm {
+            fn name(&self) -> &'static str {
+                stringify!($norm)
+            }
+
+            fn compute(&self, $a: f64, $b: f64) -> f64 {
+                $compute
+            }
+        }
+    };
+}
+
+norm!(Minimum, TNorm, |a, b| a.min(b));
+norm!(AlgebraicProduct, TNorm, |a, b| a * b);
+norm!(BoundedDifference, TNorm, |a, b| (a + b - 1.0).max(0.0));
+norm!(DrasticProduct, TNorm, |a, b| if a.max(b) == 1.0 {
+    a.min(b)
+} else {
+    0.0
+});
+norm!(EinsteinProduct, TNorm, |a, b| (a * b)
+    / (2.0 - (a + b - a * b)));
+norm!(HamacherProduct, TNorm, |a, b| if a + b == 0.0 {
+    0.0
+} else {
+    (a * b) / (a + b - a * b)
+});
+norm!(NilpotentMinimum, TNorm, |a, b| if a + b > 1.0 {
+    a.min(b)
+} else {
+    0.0
+});
+norm!(Maximum, SNorm, |a, b| a.max(b));
+norm!(AlgebraicSum, SNorm, |a, b| a + b - a * b);
+norm!(BoundedSum, SNorm, |a, b| (a + b).min(1.0));
+norm!(DrasticSum, SNorm, |a, b| if a.min(b) == 0.0 {
+    a.max(b)
+} else {
+    1.0
+});
+norm!(EinsteinSum, SNorm, |a, b| (a + b) / (1.0 + a * b));
+norm!(HamacherSum, SNorm, |a, b| if a * b == 1.0 {
+    1.0
+} else {
+    (a + b - 2.0 * a * b) / (1.0 - a * b)
+});
+norm!(NilpotentMaximum, SNorm, |a, b| if a + b < 1.0 {
+    a.max(b)
+} else {
+    1.0
+});
+norm!(NormalizedSum, SNorm, |a, b| (a + b) / a.max(b).max(1.0));
+
+/// T-norm named as in fuzzylite, `Minimum`, `AlgebraicProduct`, ...
+pub fn t_norm(name: &str) -> Option<Rc<dyn TNorm>> {
+    Some(match name {
+        "Minimum" => Rc::new(Minimum),
+        "AlgebraicProduct" => Rc::new(AlgebraicProduct),
+        "BoundedDifference" => Rc::new(BoundedDifference),
+        "DrasticProduct" => Rc::new(DrasticProduct),
+        "EinsteinProduct" => Rc::new(EinsteinProduct),
+        "HamacherProduct" => Rc::new(HamacherProduct),
+        "NilpotentMinimum" => Rc::new(NilpotentMinimum),
+        _ => return None,
+    })
+}
+
+/// S-norm named as in fuzzylite, `Maximum`, `AlgebraicSum`, ...
+pub fn s_norm(name: &str) -> Option<Rc<dyn SNorm>> {
+    Some(match name {
+        "Maximum" => Rc::new(Maximum),
+        "AlgebraicSum" => Rc::new(AlgebraicSum),
+        "BoundedSum" => Rc::new(BoundedSum),
+        "DrasticSum" => Rc::new(DrasticSum),
+        "EinsteinSum" => Rc::new(EinsteinSum),
+        "HamacherSum" => Rc::new(HamacherSum),
+        "NilpotentMaximum" => Rc::new(NilpotentMaximum),
+        "NormalizedSum" => Rc::new(NormalizedSum),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const T_NORMS: [&str; 7] = [
+        "Minimum",
+        "AlgebraicProduct",
+        "BoundedDifference",
+        "DrasticProduct",
+        "EinsteinProduct",
+        "HamacherProduct",
+        "NilpotentMinimum",
+    ];
+    const S_NORMS: [&str; 8] = [
+        "Maximum",
+        "AlgebraicSum",
+        "BoundedSum",
+        "DrasticSum",
+        "EinsteinSum",
+        "HamacherSum",
+        "NilpotentMaximum",
+        "NormalizedSum",
+    ];
+    const VALUES: [f64; 5] = [0.0, 0.25, 0.5, 0.75, 1.0];
+
+    #[test]
+    fn test_identities() {
+        for name in T_NORMS {
+            let norm = t_norm(name).unwrap();
+            assert_eq!(norm.name(), name);
+            for a in VALUES {
+                assert_eq!(norm.compute(a, 1.0), a, "{name}({a}, 1)");
+                assert_eq!(norm.compute(a, 0.0), 0.0, "{name}({a}, 0)");
+            }
+        }
+        for name in S_NORMS {
+            let norm = s_norm(name).unwrap();
+            assert_eq!(norm.name(), name);
+            for a in VALUES {
+                assert_eq!(norm.compute(a, 0.0), a, "{name}({a}, 0)");
+            }
+        }
+    }
+
+    #[test]
+    fn test_commutative() {
+        for a in VALUES {
+            for b in VALUES {
+                for name in T_NORMS {
+                    let norm = t_norm(name).unwrap();
+                    assert_eq!(norm.compute(a, b), norm.compute(b, a), "{name}");
+                }
+                for name in S_NORMS {
+                    let norm = s_norm(name).unwrap();
+                    assert_eq!(norm.compute(a, b), norm.compute(b, a), "{name}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_values() {
+        let t = |name, a, b| t_norm(name).unwrap().compute(a, b);
+        let s = |name, a, b| s_norm(name).unwrap().compute(a, b);
+        assert_eq!(t("Minimum", 0.25, 0.75), 0.25);
+        assert_eq!(t("AlgebraicProduct", 0.5, 0.5), 0.25);
+        assert_eq!(t("BoundedDifference", 0.5, 0.75), 0.25);
+        assert_eq!(t("DrasticProduct", 0.5, 0.75), 0.0);
+        assert_eq!(t("EinsteinProduct", 0.5, 0.5), 0.2);
+        assert_eq!(t("HamacherProduct", 0.5, 0.5), 1.0 / 3.0);
+        assert_eq!(t("NilpotentMinimum", 0.5, 0.75), 0.5);
+        assert_eq!(t("NilpotentMinimum", 0.25, 0.75), 0.0);
+        assert_eq!(s("Maximum", 0.25, 0.75), 0.75);
+        assert_eq!(s("AlgebraicSum", 0.5, 0.5), 0.75);
+        assert_eq!(s("BoundedSum", 0.5, 0.75), 1.0);
+        assert_eq!(s("DrasticSum", 0.5, 0.25), 1.0);
+        assert_eq!(s("EinsteinSum", 0.5, 0.5), 0.8);
+        assert_eq!(s("HamacherSum", 0.5, 0.5), 2.0 / 3.0);
+        assert_eq!(s("NilpotentMaximum", 0.25, 0.5), 0.5);
+        assert_eq!(s("NilpotentMaximum", 0.5, 0.5), 1.0);
+        assert_eq!(s("NormalizedSum", 0.5, 0.75), 1.25);
+        assert!(t_norm("Maximum").is_none());
+        assert!(s_norm("Minimum").is_none());
+    }
+}
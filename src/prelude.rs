@@ -1,4 +1,4 @@
 pub use crate::fuzzy_logic::*;
 pub use crate::fuzzy_logic::functions::*;
 pub use crate::fuzzy_logic::inference::*;
-pub use crate::fuzzy_logic::control_language::{generate_ast, FCLParser};
+pub use crate::fuzzy_logic::control_language::{generate_ast, FCLParser, FLLParser};
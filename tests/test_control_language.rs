@@ -207,3 +207,108 @@ fn test_fcl_parser() {
     }
     assert!(values.is_ok())
 }
+
+#[test]
+fn test_fcl_iec_comment() {
+    let input_text = r#"VAR_INPUT
+          (* Input variable for temperature
+             in degrees Celsius *)
+          temperature: REAL;
+        END_VAR"#;
+    let values = FCLParser::parse(Rule::var_input, input_text);
+    if let Err(error) = &values {
+        eprintln!("Error: {error:?}");
+    }
+    assert!(values.is_ok())
+}
+
+#[test]
+fn test_fcl_singleton_term() {
+    let input_text = r#"TERM high := 7.5e1;"#;
+    let values = FCLParser::parse(Rule::term, input_text);
+    if let Err(error) = &values {
+        eprintln!("Error: {error:?}");
+    }
+    assert!(values.is_ok())
+}
+
+#[test]
+fn test_fcl_defuzzify_range_default() {
+    let input_text = r#"DEFUZZIFY heating
+                          TERM off := (0, 1) (20, 1) (40, 0);
+                          TERM high := 100;
+                          METHOD: COGS;
+                          DEFAULT := NC;
+                          RANGE := (0 .. 100);
+                        END_DEFUZZIFY"#;
+    let values = FCLParser::parse(Rule::defuzzify, input_text);
+    if let Err(error) = &values {
+        eprintln!("Error: {error:?}");
+    }
+    assert!(values.is_ok())
+}
+
+#[test]
+fn test_fcl_all_operators_parsing() {
+    let input_text = r#"
+          AND: BDIF;
+          OR: ASUM;
+          ACT: PROD;
+          ACCU: NSUM;
+    "#;
+    let values = FCLParser::parse(Rule::operators, input_text);
+    if let Err(error) = &values {
+        eprintln!("Error: {error:?}");
+    }
+    assert!(values.is_ok())
+}
+
+#[test]
+fn test_fcl_rule_not_parentheses_weight() {
+    let input_text = r#"RULE 1 : IF temperature IS NOT cold AND (humidity IS low OR NOT (humidity IS high)) THEN heating IS high, fan IS off WITH 0.8;"#;
+    let values = FCLParser::parse(Rule::rule, input_text);
+    if let Err(error) = &values {
+        eprintln!("Error: {error:?}");
+    }
+    assert!(values.is_ok())
+}
+
+#[test]
+fn test_fcl_keyword_is_not_identifier() {
+    let input_text = r#"RULE 1 : IF temperature IS cold THEN IS IS high;"#;
+    let values = FCLParser::parse(Rule::rule, input_text);
+    assert!(values.is_err())
+}
+
+mod fll {
+    use pest::Parser;
+    use pest_derive::Parser;
+
+    #[derive(Parser)]
+    #[grammar = "src/fuzzy_logic/grammar/fll.pest"]
+    pub struct FLLParser;
+
+    #[test]
+    fn test_fll_term() {
+        let values = FLLParser::parse(Rule::term, "term: left Ramp 1.000 0.000\n");
+        assert!(values.is_ok(), "{values:?}");
+    }
+
+    #[test]
+    fn test_fll_defuzzifier() {
+        assert!(FLLParser::parse(Rule::defuzzifier, "defuzzifier: Centroid 100").is_ok());
+        assert!(FLLParser::parse(Rule::defuzzifier, "defuzzifier: WeightedAverage TakagiSugeno").is_ok());
+    }
+
+    #[test]
+    fn test_fll_rule_hedges() {
+        let input = "rule: if obstacle is not very left and speed is any then steer is right with 0.5";
+        let values = FLLParser::parse(Rule::rule, input);
+        assert!(values.is_ok(), "{values:?}");
+    }
+
+    #[test]
+    fn test_fll_keyword_is_not_variable() {
+        assert!(FLLParser::parse(Rule::proposition, "is is left").is_err());
+    }
+}
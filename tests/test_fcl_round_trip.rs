@@ -0,0 +1,108 @@
+use std::rc::Rc;
+
+use rtee::prelude::*;
+
+const PROGRAM: &str = include_str!("../examples/fuzzy_program.fcl");
+
+/// System built with the Rust API, equivalent to a small service quality policy
+fn service_policy() -> Mamdani {
+    let poor = FuzzySet::from_points("poor", vec![(0.0, 1.0), (2.5, 1.0), (5.0, 0.0)]);
+    let good = FuzzySet::from_points(
+        "good",
+        vec![(2.5, 0.0), (5.0, 1.0), (7.5, 1.0), (10.0, 0.0)],
+    );
+    let cheap = FuzzySet::from_points("cheap", vec![(0.0, 0.0), (5.0, 1.0), (10.0, 0.0)]);
+    let generous = FuzzySet::from_points("generous", vec![(10.0, 0.0), (15.0, 1.0), (20.0, 0.0)]);
+    let service = FuzzyVariable::new("service", vec![poor.clone(), good.clone()]);
+    let food = FuzzyVariable::new("food", vec![poor.clone(), good.clone()]);
+    let tip = FuzzyVariable::new("tip", vec![cheap.clone(), generous.clone()])
+        .with_defuzzifier(Rc::new(defuzzifier::MeanOfMaximum::default()))
+        .with_lock_previous(true);
+    let rating = FuzzyVariable::new("rating", vec![poor.clone(), good.clone()]).with_default(5.0);
+    let not_good = RuleExpr::Not(Box::new(RuleExpr::is("food", "good")));
+    let rules = vec![
+        Rule::new(&[("service", "poor")], ("tip", "cheap"))
+            .with_consequent(("rating", "poor"))
+            .with_weight(0.75),
+        Rule::with_condition(
+            RuleExpr::And(vec![
+                RuleExpr::is("service", "good"),
+                RuleExpr::Or(vec![RuleExpr::is("food", "poor"), not_good]),
+            ]),
+            ("tip", "cheap"),
+        ),
+        Rule::new(&[("service", "good"), ("food", "good")], ("tip", "generous"))
+            .with_consequent(("rating", "good")),
+    ];
+    Mamdani::new(vec![service, food], vec![tip, rating], rules).unwrap()
+}
+
+fn assert_equivalent(left: &Mamdani, right: &Mamdani) {
+    let variables = |system| -> Vec<_> {
+        let system: &Mamdani = system;
+        system.inputs().iter().chain(system.outputs()).collect()
+    };
+    assert_eq!(variables(left).len(), variables(right).len());
+    for (left, right) in variables(left).into_iter().zip(variables(right)) {
+        let terms = |variable: &FuzzyVariable| -> Vec<_> {
+            let sets = variable.fuzzy_sets().iter();
+            sets.map(|set| (set.name.clone(), set.function.points()))
+                .collect()
+        };
+        assert_eq!(left.name(), right.name());
+        assert_eq!(left.defuzzifier.name(), right.defuzzifier.name());
+        assert_eq!(terms(left), terms(right));
+        assert_eq!(left.lock_previous(), right.lock_previous());
+        let (default, other) = (left.default(), right.default());
+        assert!(default == other || (default.is_nan() && other.is_nan()));
+    }
+    let weights = |system: &Mamdani| -> Vec<_> { system.rules().map(Rule::weight).collect() };
+    assert_eq!(weights(left), weights(right));
+    for (left, right) in left.rule_blocks().iter().zip(right.rule_blocks()) {
+        assert_eq!(left.conjunction.name(), right.conjunction.name());
+        assert_eq!(left.implication.name(), right.implication.name());
+        assert_eq!(left.accumulation.name(), right.accumulation.name());
+    }
+    for x in 0..=10 {
+        for y in 0..=10 {
+            let inputs = [x as f64 * 10.0, y as f64 * 10.0];
+            let (left, right) = (left.infer_outputs(&inputs), right.infer_outputs(&inputs));
+            for ((name, left), (_, right)) in left.iter().zip(right.iter()) {
+                // No rule fires outside of the terms of the inputs
+                assert!(
+                    left == right || (left.is_nan() && right.is_nan()),
+                    "{name} {inputs:?}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_round_trip_program() {
+    let system = compile_fcl(PROGRAM).unwrap();
+    let fcl = write_fcl("RoomTempController", &system).unwrap();
+    assert_equivalent(&system, &compile_fcl(&fcl).unwrap());
+}
+
+#[test]
+fn test_round_trip_rust_system() {
+    let system = service_policy();
+    let fcl = write_fcl("ServicePolicy", &system).unwrap();
+    assert!(fcl.contains("IF service IS good AND (food IS poor OR food IS NOT good) THEN"));
+    assert!(fcl.contains("THEN tip IS generous, rating IS good;"));
+    let compiled = compile_fcl(&fcl).unwrap();
+    assert_equivalent(&system, &compiled);
+    assert_eq!(fcl, write_fcl("ServicePolicy", &compiled).unwrap());
+}
+
+#[test]
+fn test_round_trip_controller() {
+    let mut controller = FuzzyController::new(service_policy());
+    let fcl = controller.to_fcl("ServicePolicy").unwrap();
+    let mut parsed = FuzzyController::from_fcl(&fcl).unwrap();
+    assert_eq!(
+        controller.evaluate_outputs(&[6.0, 8.0]),
+        parsed.evaluate_outputs(&[6.0, 8.0])
+    );
+}